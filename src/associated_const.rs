@@ -2,7 +2,7 @@ use crate::bound::Bound;
 use crate::r#type::Type;
 
 /// Defines an associated constant.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AssociatedConst(pub Bound);
 
 impl AssociatedConst {
@@ -1,17 +1,103 @@
-use crate::bound::Bound;
-use crate::r#type::Type;
+use std::fmt::{self, Write};
 
-/// Defines an associated type.
-#[derive(Debug, Clone)]
-pub struct AssociatedType(pub Bound);
+use crate::bound::{push_bound, Bound};
+use crate::formatter::{fmt_bound_rhs, fmt_bounds_ending, fmt_generics, Formatter};
+
+use crate::r#type::{lifetime_name, Type};
+
+/// Defines an associated type of a trait, e.g. `type Item: Clone;` or the
+/// generic associated type `type Iter<'a>: Iterator where Self: 'a;`, or of
+/// an impl block, e.g. `type Iter<'a> = std::slice::Iter<'a, u8>;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssociatedType {
+    /// Name and bounds of the associated type
+    bound: Bound,
+
+    /// Generics of a generic associated type
+    generics: Vec<String>,
+
+    /// `where` bounds of a generic associated type
+    where_bounds: Vec<Bound>,
+
+    /// The type assigned by an impl block
+    value: Option<Type>,
+}
 
 impl AssociatedType {
+    pub(crate) fn new(name: impl ToString) -> Self {
+        AssociatedType {
+            bound: Bound {
+                name: name.to_string(),
+                bound: vec![],
+                lhs: None,
+            },
+            generics: vec![],
+            where_bounds: vec![],
+            value: None,
+        }
+    }
+
+    pub(crate) fn with_value(name: impl ToString, ty: Type) -> Self {
+        AssociatedType {
+            value: Some(ty),
+            ..AssociatedType::new(name)
+        }
+    }
+
     /// Add a bound to the associated type.
+    ///
+    /// Bounds are only emitted by trait declarations.
     pub fn bound<T>(&mut self, ty: T) -> &mut Self
     where
         T: Into<Type>,
     {
-        self.0.bound.push(ty.into());
+        self.bound.bound.push(ty.into());
+        self
+    }
+
+    /// Add a generic, making this a generic associated type.
+    pub fn generic(&mut self, name: impl ToString) -> &mut Self {
+        self.generics.push(name.to_string());
+        self
+    }
+
+    /// Add a lifetime, making this a generic associated type, e.g. `'a`.
+    ///
+    /// Lifetimes are emitted before the other generics.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        let index = self.generics.iter().take_while(|g| g.starts_with('\'')).count();
+        self.generics.insert(index, lifetime_name(name));
+        self
+    }
+
+    /// Add a `where` bound to the associated type, e.g. `Self: 'a`.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
+    pub fn where_bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        push_bound(&mut self.where_bounds, name.to_string(), ty.into());
         self
     }
+
+    /// Formats the associated type using the given formatter.
+    pub(crate) fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        write!(fmt, "type {}", self.bound.name)?;
+        fmt_generics(&self.generics, fmt)?;
+
+        if let Some(ref ty) = self.value {
+            write!(fmt, " = ")?;
+            ty.fmt(fmt)?;
+        } else if !self.bound.bound.is_empty() {
+            write!(fmt, ": ")?;
+            fmt_bound_rhs(&self.bound.bound, fmt)?;
+        }
+
+        if self.where_bounds.is_empty() {
+            writeln!(fmt, ";")
+        } else {
+            fmt_bounds_ending(&self.where_bounds, ";", fmt)
+        }
+    }
 }
@@ -4,7 +4,7 @@ use crate::body::Body;
 use crate::formatter::Formatter;
 
 /// Defines a code block. This is used to define a function body.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Block {
     before: Option<String>,
     after: Option<String>,
@@ -22,6 +22,8 @@ impl Block {
     }
 
     /// Push a line to the code block.
+    ///
+    /// Each line of a multi-line string is indented to the level of the block.
     pub fn line<T>(&mut self, line: T) -> &mut Self
     where
         T: ToString,
@@ -36,6 +38,16 @@ impl Block {
         self
     }
 
+    /// Push a nested `unsafe { .. }` block, filled in by `f`.
+    pub fn unsafe_block<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Block),
+    {
+        let mut block = Block::new("unsafe");
+        f(&mut block);
+        self.push_block(block)
+    }
+
     /// Add a snippet after the block.
     pub fn after(&mut self, after: impl ToString) -> &mut Self {
         self.after = Some(after.to_string());
@@ -54,7 +66,7 @@ impl Block {
             write!(fmt, " ")?;
         }
 
-        write!(fmt, "{{\n")?;
+        writeln!(fmt, "{{")?;
 
         fmt.indent(|fmt| {
             for b in &self.body {
@@ -70,7 +82,7 @@ impl Block {
             write!(fmt, "{}", after)?;
         }
 
-        write!(fmt, "\n")?;
+        writeln!(fmt)?;
         Ok(())
     }
 }
@@ -3,7 +3,7 @@ use std::fmt::{self, Write};
 use crate::block::Block;
 use crate::formatter::Formatter;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Body {
     String(String),
     Block(Block),
@@ -12,7 +12,7 @@ pub enum Body {
 impl Body {
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         match &self {
-            Body::String(s) => write!(fmt, "{}\n", s),
+            Body::String(s) => writeln!(fmt, "{}", s),
             Body::Block(b) => b.fmt(fmt),
         }
     }
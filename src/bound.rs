@@ -1,7 +1,35 @@
+use crate::formatter::Formatter;
 use crate::r#type::Type;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Bound {
     pub name: String,
     pub bound: Vec<Type>,
+
+    /// The bounded type, rendered instead of `name` when set, e.g. `T::Item`
+    pub lhs: Option<Type>,
+}
+
+/// Add `ty` to the bounds of `name`, so that all bounds on the same name are
+/// formatted as a single predicate, e.g. `T: Clone + Debug`.
+pub(crate) fn push_bound(bounds: &mut Vec<Bound>, name: String, ty: Type) {
+    match bounds.iter_mut().find(|bound| bound.name == name) {
+        Some(bound) => bound.bound.push(ty),
+        None => bounds.push(Bound {
+            name,
+            bound: vec![ty],
+            lhs: None,
+        }),
+    }
+}
+
+/// Add `ty` to the bounds of the type `lhs`, like [`push_bound`].
+pub(crate) fn push_bound_ty(bounds: &mut Vec<Bound>, lhs: Type, ty: Type) {
+    let mut name = String::new();
+    lhs.fmt(&mut Formatter::new(&mut name)).unwrap();
+
+    push_bound(bounds, name.clone(), ty);
+    if let Some(bound) = bounds.iter_mut().find(|bound| bound.name == name) {
+        bound.lhs.get_or_insert(lhs);
+    }
 }
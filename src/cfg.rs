@@ -0,0 +1,103 @@
+use std::fmt::{self, Display};
+
+/// Defines a configuration predicate, as used by `#[cfg(..)]` and
+/// `#[cfg_attr(..)]`.
+///
+/// The predicate renders through its `Display` implementation, so it can be
+/// passed to every `cfg` and `cfg_attr` method:
+///
+/// ```
+/// use codegen::{Cfg, Struct};
+///
+/// let mut foo = Struct::new("Foo");
+///
+/// // #[cfg(all(feature = "a", not(windows)))]
+/// foo.cfg(Cfg::all([Cfg::feature("a"), Cfg::not(Cfg::name("windows"))]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg {
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    /// A configuration option such as `test` or `unix`.
+    Name(String),
+    /// A key-value option such as `target_os = "linux"`.
+    KeyValue(String, String),
+    /// `all(..)` of the predicates.
+    All(Vec<Cfg>),
+    /// `any(..)` of the predicates.
+    Any(Vec<Cfg>),
+    /// `not(..)` of the predicate.
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    /// Return a predicate on a configuration option, e.g. `test`.
+    pub fn name(name: impl ToString) -> Self {
+        Cfg {
+            kind: Kind::Name(name.to_string()),
+        }
+    }
+
+    /// Return a predicate on a key-value configuration option, e.g.
+    /// `target_os = "linux"`.
+    pub fn key_value(key: impl ToString, value: impl ToString) -> Self {
+        Cfg {
+            kind: Kind::KeyValue(key.to_string(), value.to_string()),
+        }
+    }
+
+    /// Return a predicate on an enabled feature, e.g. `feature = "serde"`.
+    pub fn feature(name: impl ToString) -> Self {
+        Cfg::key_value("feature", name)
+    }
+
+    /// Return a predicate that holds if all of `cfgs` hold.
+    pub fn all(cfgs: impl IntoIterator<Item = Cfg>) -> Self {
+        Cfg {
+            kind: Kind::All(cfgs.into_iter().collect()),
+        }
+    }
+
+    /// Return a predicate that holds if any of `cfgs` holds.
+    pub fn any(cfgs: impl IntoIterator<Item = Cfg>) -> Self {
+        Cfg {
+            kind: Kind::Any(cfgs.into_iter().collect()),
+        }
+    }
+
+    /// Return a predicate that holds if `cfg` does not.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(cfg: Cfg) -> Self {
+        Cfg {
+            kind: Kind::Not(Box::new(cfg)),
+        }
+    }
+}
+
+impl Display for Cfg {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Name(ref name) => write!(fmt, "{}", name),
+            Kind::KeyValue(ref key, ref value) => write!(fmt, "{} = {:?}", key, value),
+            Kind::All(ref cfgs) => fmt_list("all", cfgs, fmt),
+            Kind::Any(ref cfgs) => fmt_list("any", cfgs, fmt),
+            Kind::Not(ref cfg) => write!(fmt, "not({})", cfg),
+        }
+    }
+}
+
+fn fmt_list(op: &str, cfgs: &[Cfg], fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(fmt, "{}(", op)?;
+
+    for (i, cfg) in cfgs.iter().enumerate() {
+        if i != 0 {
+            write!(fmt, ", ")?;
+        }
+        write!(fmt, "{}", cfg)?;
+    }
+
+    write!(fmt, ")")
+}
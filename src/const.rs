@@ -0,0 +1,143 @@
+use std::fmt::{self, Write};
+
+use crate::docs::Docs;
+use crate::formatter::{fmt_value, Formatter, MultilineStr};
+use crate::visibility::Visibility;
+
+use crate::r#type::Type;
+
+/// Defines a `const` or `static` item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Const {
+    /// Name of the item
+    name: String,
+
+    /// Type of the item
+    ty: Type,
+
+    /// Value expression, emitted verbatim unless it is a multi-line string
+    /// literal
+    value: String,
+
+    /// How a multi-line string value is rendered
+    multiline_str: MultilineStr,
+
+    /// Visibility
+    vis: Option<String>,
+
+    /// Documentation
+    docs: Option<Docs>,
+
+    /// `cfg` attributes to conditionally compile the item
+    cfg: Vec<String>,
+
+    /// Whether the item is a `static` instead of a `const`
+    r#static: bool,
+
+    /// Whether the `static` is mutable
+    mutable: bool,
+}
+
+impl Const {
+    /// Return a new `const` item with the given name, type and value.
+    pub fn new<T>(name: impl ToString, ty: T, value: impl ToString) -> Self
+    where
+        T: Into<Type>,
+    {
+        Const {
+            name: name.to_string(),
+            ty: ty.into(),
+            value: value.to_string(),
+            multiline_str: MultilineStr::default(),
+            vis: None,
+            docs: None,
+            cfg: Vec::new(),
+            r#static: false,
+            mutable: false,
+        }
+    }
+
+    /// Returns the name of the item.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Set the item visibility.
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.vis = vis.into().into_modifier();
+        self
+    }
+
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.vis.get_or_insert_with(|| vis.to_string());
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.vis.is_some()
+    }
+
+    /// Set the item documentation.
+    pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.docs = Some(Docs::new(docs));
+        self
+    }
+
+    /// Add a `cfg` attribute to conditionally compile the item.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
+    /// Set how a multi-line string value is rendered.
+    pub fn multiline_str(&mut self, style: MultilineStr) -> &mut Self {
+        self.multiline_str = style;
+        self
+    }
+
+    /// Set whether the item is a `static` instead of a `const`.
+    pub fn set_static(&mut self, r#static: bool) -> &mut Self {
+        self.r#static = r#static;
+        self
+    }
+
+    /// Set whether the item is a `static mut`.
+    ///
+    /// This implies [`Const::set_static`].
+    pub fn set_mut(&mut self, mutable: bool) -> &mut Self {
+        self.mutable = mutable;
+        if mutable {
+            self.r#static = true;
+        }
+        self
+    }
+
+    /// Formats the item using the given formatter.
+    pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(ref docs) = self.docs {
+            docs.fmt(fmt)?;
+        }
+
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
+        }
+
+        if let Some(ref vis) = self.vis {
+            write!(fmt, "{} ", vis)?;
+        }
+
+        match (self.r#static, self.mutable) {
+            (true, true) => write!(fmt, "static mut ")?,
+            (true, false) => write!(fmt, "static ")?,
+            _ => write!(fmt, "const ")?,
+        }
+
+        write!(fmt, "{}: ", self.name)?;
+        self.ty.fmt(fmt)?;
+        write!(fmt, " = ")?;
+        fmt_value(&self.value, self.multiline_str, fmt)?;
+        writeln!(fmt, ";")
+    }
+}
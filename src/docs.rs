@@ -2,7 +2,7 @@ use std::fmt::{self, Write};
 
 use crate::formatter::Formatter;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Docs {
     docs: String,
 }
@@ -15,8 +15,18 @@ impl Docs {
     }
 
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_prefix("///", fmt)
+    }
+
+    /// Formats the docs as inner doc comments (`//!`), documenting the
+    /// enclosing module.
+    pub fn fmt_inner(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_prefix("//!", fmt)
+    }
+
+    fn fmt_with_prefix(&self, prefix: &str, fmt: &mut Formatter<'_>) -> fmt::Result {
         for line in self.docs.lines() {
-            write!(fmt, "///")?;
+            write!(fmt, "{}", prefix)?;
             if !line.is_empty() {
                 write!(fmt, " {}", line)?;
             }
@@ -26,8 +36,14 @@ impl Docs {
         Ok(())
     }
 
+    /// Append `other` to the docs, separated by a newline if both are
+    /// non-empty.
     pub fn append(&mut self, other: impl ToString) -> &mut Self {
-        self.docs.push_str(&other.to_string());
+        let other = other.to_string();
+        if !self.docs.is_empty() && !other.is_empty() && !self.docs.ends_with('\n') {
+            self.docs.push('\n');
+        }
+        self.docs.push_str(&other);
         self
     }
 
@@ -35,3 +51,50 @@ impl Docs {
         &self.docs
     }
 }
+
+/// Strip the leading whitespace common to all non-blank lines of `docs`.
+///
+/// This is useful for documentation written as an indented string literal,
+/// e.g. `scope.new_struct("Foo").doc(dedent(DOCS))`. Blank lines are emptied
+/// and a leading newline is dropped.
+pub fn dedent(docs: &str) -> String {
+    let docs = docs.strip_prefix('\n').unwrap_or(docs);
+
+    let margin = docs
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|margin, indent| {
+            let common = margin
+                .char_indices()
+                .zip(indent.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(margin.len().min(indent.len()), |((i, _), _)| i);
+            &margin[..common]
+        })
+        .unwrap_or("");
+
+    docs.lines()
+        .map(|line| line.strip_prefix(margin).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn append() {
+    let mut docs = Docs::new("");
+    docs.append("first").append("").append("second\n").append("third");
+    assert_eq!(docs.to_str(), "first\nsecond\nthird");
+}
+
+#[test]
+fn dedent_common_indentation() {
+    let docs = "
+        First line.
+
+          Indented more.
+        Last line.
+    ";
+    assert_eq!(dedent(docs), "First line.\n\n  Indented more.\nLast line.\n");
+    assert_eq!(dedent("no indent\n  some"), "no indent\n  some");
+}
@@ -1,13 +1,17 @@
 use std::fmt;
 
+use crate::block::Block;
+use crate::fields::Fields;
 use crate::formatter::Formatter;
+use crate::visibility::Visibility;
+use crate::r#impl::Impl;
 use crate::type_def::TypeDef;
 use crate::variant::Variant;
 
 use crate::r#type::Type;
 
 /// Defines an enumeration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Enum {
     type_def: TypeDef,
     variants: Vec<Variant>,
@@ -28,18 +32,41 @@ impl Enum {
     }
 
     /// Set the enum visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.type_def.vis(vis);
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
         self
     }
 
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
     /// Add a generic to the enum.
+    ///
+    /// Generics that were already added are ignored.
     pub fn generic(&mut self, name: impl ToString) -> &mut Self {
-        self.type_def.ty.generic(name);
+        self.type_def.generic(name);
+        self
+    }
+
+    /// Add a lifetime to the enum, e.g. `'a`.
+    ///
+    /// Lifetimes are emitted before the other generics.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.lifetime(name);
         self
     }
 
     /// Add a `where` bound to the enum.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
@@ -48,18 +75,58 @@ impl Enum {
         self
     }
 
+    /// Sort the `where` clause predicates by name when formatting the enum.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_bounds(sort);
+        self
+    }
+
     /// Set the enum documentation.
     pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
         self.type_def.doc(docs);
         self
     }
 
+    /// Add a `#[doc = <expr>]` attribute whose value is an expression rather
+    /// than a string literal, e.g. `concat!("Since ", env!("CARGO_PKG_VERSION"))`.
+    pub fn doc_expr(&mut self, expr: impl ToString) -> &mut Self {
+        self.type_def.doc_expr(expr);
+        self
+    }
+
     /// Add a new type that the struct should derive.
+    ///
+    /// Derives that were already added are ignored.
     pub fn derive(&mut self, name: impl ToString) -> &mut Self {
         self.type_def.derive(name);
         self
     }
 
+    /// Sort the derives alphabetically when formatting the enum.
+    pub fn sort_derives(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_derives(sort);
+        self
+    }
+
+    /// Emit each derive of the enum as its own `#[derive(..)]` attribute.
+    pub fn split_derives(&mut self, split: bool) -> &mut Self {
+        self.type_def.split_derives(split);
+        self
+    }
+
+    /// Add a `#[serde(rename_all = "...")]` attribute, e.g. with `camelCase`.
+    pub fn serde_rename_all(&mut self, case: impl ToString) -> &mut Self {
+        self.type_def.attr(format!("serde(rename_all = {:?})", case.to_string()));
+        self
+    }
+
+    /// Emit `#[rustfmt::skip]` above the enum, e.g. to keep a hand-aligned
+    /// layout.
+    pub fn skip_rustfmt(&mut self) -> &mut Self {
+        self.type_def.skip_rustfmt();
+        self
+    }
+
     /// Specify lint attribute to supress a warning or error.
     pub fn allow(&mut self, allow: impl ToString) -> &mut Self {
         self.type_def.allow(allow);
@@ -84,12 +151,25 @@ impl Enum {
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the enum.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
     /// Add a cfg_attr to include attributes based on a configuration predicate
     pub fn cfg_attr(&mut self, cfg_attr: impl ToString) -> &mut Self {
         self.type_def.cfg_attr(cfg_attr);
         self
     }
 
+    /// Add a cfg_attr from a configuration predicate and the attribute to
+    /// include when it holds, e.g. `#[cfg_attr(test, derive(PartialEq))]`.
+    pub fn cfg_attr_with(&mut self, cond: impl ToString, content: impl ToString) -> &mut Self {
+        self.type_def.cfg_attr_with(cond, content);
+        self
+    }
+
     /// Push a variant to the enum, returning a mutable reference to it.
     pub fn new_variant(&mut self, name: impl ToString) -> &mut Variant {
         self.push_variant(Variant::new(name.to_string()));
@@ -102,6 +182,159 @@ impl Enum {
         self
     }
 
+    /// Mark the variant `name` with `#[default]` and derive `Default` for the
+    /// enum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the enum has no variant `name`, if the variant has fields
+    /// or if another variant is already the default.
+    pub fn default_variant(&mut self, name: &str) -> &mut Self {
+        assert!(
+            !self.variants.iter().any(|variant| variant.name() != name && variant.has_annotation("#[default]")),
+            "enum already has a default variant"
+        );
+
+        let variant = self
+            .variants
+            .iter_mut()
+            .find(|variant| variant.name() == name)
+            .unwrap_or_else(|| panic!("enum has no variant `{}`", name));
+        assert!(variant.fields().is_empty(), "the default variant must be fieldless");
+
+        if !variant.has_annotation("#[default]") {
+            variant.annotation("#[default]");
+        }
+        if !self.type_def.has_derive("Default") {
+            self.type_def.derive("Default");
+        }
+        self
+    }
+
+    /// Generate an `impl` block with a method casting the enum to the given
+    /// integer type, e.g. `pub fn as_u8(&self) -> u8 { *self as u8 }`.
+    ///
+    /// The cast requires the enum to be `Copy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any variant of the enum has fields.
+    pub fn generate_discriminant_fn(&self, int_ty: impl ToString) -> Impl {
+        assert!(
+            self.variants.iter().all(|variant| variant.fields().is_empty()),
+            "discriminant casts require a fieldless enum"
+        );
+
+        let int_ty = int_ty.to_string();
+        let mut imp = Impl::new(self.ty());
+        imp.new_fn(format!("as_{}", int_ty))
+            .vis("pub")
+            .arg_ref_self()
+            .ret(&int_ty)
+            .line(format!("*self as {}", int_ty));
+
+        imp
+    }
+
+    /// Generate a manual `impl Clone` block for the enum, cloning the fields
+    /// of each variant in a `match self` arm.
+    ///
+    /// Unlike `#[derive(Clone)]`, a generic parameter is only bound by
+    /// `Clone` if it is used outside of `PhantomData`.
+    pub fn generate_clone(&self) -> Impl {
+        let mut imp = Impl::new(self.ty());
+        for generic in self.ty().generics() {
+            imp.generic(generic.name());
+
+            let used = self
+                .variants
+                .iter()
+                .flat_map(|variant| variant.fields().iter_types())
+                .any(|ty| uses_outside_phantom_data(ty, generic.name()));
+            if used {
+                imp.bound(generic.name(), "Clone");
+            }
+        }
+        imp.impl_trait("Clone");
+
+        let mut block = Block::new("match self");
+        for variant in &self.variants {
+            let name = variant.name();
+            match *variant.fields() {
+                Fields::Empty => {
+                    block.line(format!("Self::{name} => Self::{name},", name = name));
+                }
+                Fields::Tuple(ref tys) => {
+                    let bindings = (0..tys.len()).map(|i| format!("f{}", i)).collect::<Vec<_>>();
+                    let clones = bindings.iter().map(|b| format!("{}.clone()", b)).collect::<Vec<_>>();
+                    block.line(format!(
+                        "Self::{name}({}) => Self::{name}({}),",
+                        bindings.join(", "),
+                        clones.join(", "),
+                        name = name
+                    ));
+                }
+                Fields::Named(ref fields) => {
+                    let bindings = fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+                    let clones = bindings.iter().map(|b| format!("{b}: {b}.clone()", b = b)).collect::<Vec<_>>();
+                    block.line(format!(
+                        "Self::{name} {{ {} }} => Self::{name} {{ {} }},",
+                        bindings.join(", "),
+                        clones.join(", "),
+                        name = name
+                    ));
+                }
+            }
+        }
+
+        imp.new_fn("clone").arg_ref_self().ret("Self").push_block(block);
+
+        imp
+    }
+
+    /// Generate an `impl FromStr` block for a fieldless enum, matching each
+    /// variant's name, or the name set by a `#[serde(rename = "...")]`
+    /// annotation, e.g. `"red" => Ok(Self::Red),`. Variants without a
+    /// rename follow the enum's [`serde_rename_all`](Self::serde_rename_all)
+    /// case.
+    ///
+    /// Other strings are converted into `err_ty` with `Into`, so it must
+    /// implement `From<&str>`, e.g. `String`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any variant of the enum has fields.
+    pub fn generate_from_str<T>(&self, err_ty: T) -> Impl
+    where
+        T: Into<Type>,
+    {
+        assert!(
+            self.variants.iter().all(|variant| variant.fields().is_empty()),
+            "`FromStr` can only be generated for a fieldless enum"
+        );
+
+        let mut imp = Impl::new(self.ty());
+        for generic in self.ty().generics() {
+            imp.generic(generic.name());
+        }
+        imp.impl_trait("std::str::FromStr").associate_type("Err", err_ty);
+
+        let rename_all = self.type_def.serde_rename_all();
+        let mut block = Block::new("match s");
+        for variant in &self.variants {
+            let s = variant.serde_name(rename_all);
+            block.line(format!("{:?} => Ok(Self::{}),", s, variant.name()));
+        }
+        block.line("_ => Err(s.into()),");
+
+        imp.new_fn("from_str")
+            .arg("s", "&str")
+            .ret("Result<Self, Self::Err>")
+            .push_block(block);
+
+        imp
+    }
+
     /// Formats the enum using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         self.type_def.fmt_head("enum", &[], fmt)?;
@@ -115,3 +348,13 @@ impl Enum {
         })
     }
 }
+
+/// Returns `true` if `ty` mentions the generic `name` outside of a
+/// `PhantomData`.
+fn uses_outside_phantom_data(ty: &Type, name: &str) -> bool {
+    if ty.name() == "PhantomData" || ty.name().ends_with("::PhantomData") {
+        return false;
+    }
+
+    ty.name() == name || ty.generics().iter().any(|ty| uses_outside_phantom_data(ty, name))
+}
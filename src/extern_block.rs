@@ -0,0 +1,85 @@
+use std::fmt::{self, Write};
+
+use crate::formatter::Formatter;
+use crate::function::Function;
+
+/// Defines an `extern` block of foreign function declarations, e.g.
+/// `extern "C" { .. }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternBlock {
+    /// The ABI of the block
+    abi: String,
+
+    /// Function declarations
+    fns: Vec<Function>,
+
+    /// `cfg` attributes to conditionally compile the block
+    cfg: Vec<String>,
+}
+
+impl ExternBlock {
+    /// Return a new `extern` block with the given ABI, e.g. `"C"`.
+    pub fn new(abi: impl ToString) -> Self {
+        ExternBlock {
+            abi: abi.to_string(),
+            fns: vec![],
+            cfg: vec![],
+        }
+    }
+
+    /// Returns the ABI of the block.
+    pub fn abi(&self) -> &String {
+        &self.abi
+    }
+
+    /// Add a `cfg` attribute to conditionally compile the block, e.g.
+    /// `target_os = "linux"`.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
+    /// Push a new function declaration, returning a mutable reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Formatting the block panics if a body is added to the function, e.g.
+    /// with [`Function::line`].
+    pub fn new_fn(&mut self, name: impl ToString) -> &mut Function {
+        let mut func = Function::new(name);
+        func.body = None;
+        self.push_fn(func);
+        self.fns.last_mut().unwrap()
+    }
+
+    /// Push a function declaration.
+    ///
+    /// # Panics
+    ///
+    /// Formatting the block panics if the function has a body, see
+    /// [`Function::remove_body`].
+    pub fn push_fn(&mut self, item: Function) -> &mut Self {
+        self.fns.push(item);
+        self
+    }
+
+    /// Formats the `extern` block using the given formatter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a function of the block has a body.
+    pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
+        }
+        write!(fmt, "extern \"{}\"", self.abi)?;
+
+        fmt.block(|fmt| {
+            for func in &self.fns {
+                func.fmt_foreign(fmt)?;
+            }
+
+            Ok(())
+        })
+    }
+}
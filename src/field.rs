@@ -1,7 +1,9 @@
+use crate::visibility::Visibility;
+
 use crate::r#type::Type;
 
 /// Defines a struct field.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Field {
     /// Field name
     pub name: String,
@@ -15,11 +17,14 @@ pub struct Field {
     /// Field annotation
     pub annotation: Vec<String>,
 
-    /// Field value
+    /// Field value, emitted for the associated constants of an impl block
     pub value: String,
 
     /// The visibility of the field
     pub visibility: Option<String>,
+
+    /// Expression used for the field by a generated `Default` impl
+    pub default_expr: Option<String>,
 }
 
 impl Field {
@@ -35,6 +40,7 @@ impl Field {
             annotation: Vec::new(),
             value: String::new(),
             visibility: None,
+            default_expr: None,
         }
     }
 
@@ -50,9 +56,88 @@ impl Field {
         self
     }
 
+    /// Set field's value.
+    ///
+    /// The value is emitted for an associated constant pushed with
+    /// [`Impl::push_associate_const`](crate::Impl::push_associate_const).
+    /// Struct fields cannot have a value and ignore it.
+    pub fn value(&mut self, value: impl ToString) -> &mut Self {
+        self.value = value.to_string();
+        self
+    }
+
+    /// Add a `#[serde(skip_serializing_if = "...")]` annotation, where `path`
+    /// is the function deciding whether to skip the field, e.g.
+    /// `Option::is_none`.
+    pub fn serde_skip_if(&mut self, path: impl ToString) -> &mut Self {
+        self.annotation(format!(
+            "#[serde(skip_serializing_if = {:?})]",
+            path.to_string()
+        ))
+    }
+
+    /// Set the expression used for the field by a generated `Default` impl.
+    ///
+    /// See [`Struct::generate_default`](crate::Struct::generate_default).
+    pub fn default_expr(&mut self, expr: impl ToString) -> &mut Self {
+        self.default_expr = Some(expr.to_string());
+        self
+    }
+
+    /// Set the visibility of the field
+    pub fn vis(&mut self, visibility: impl Into<Visibility>) -> &mut Self {
+        self.visibility = visibility.into().into_modifier();
+        self
+    }
+}
+
+/// Defines an unnamed field of a tuple struct or variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleField {
+    /// Field type
+    pub ty: Type,
+
+    /// Field documentation
+    pub documentation: String,
+
+    /// Field annotation
+    pub annotation: Vec<String>,
+
+    /// The visibility of the field
+    pub visibility: Option<String>,
+}
+
+impl TupleField {
+    /// Return a tuple field definition with the provided type
+    pub fn new<T>(ty: T) -> Self
+    where
+        T: Into<Type>,
+    {
+        TupleField {
+            ty: ty.into(),
+            documentation: String::new(),
+            annotation: Vec::new(),
+            visibility: None,
+        }
+    }
+
+    /// Set field's documentation.
+    ///
+    /// Tuple fields with documentation are formatted one per line.
+    pub fn doc(&mut self, documentation: impl ToString) -> &mut Self {
+        self.documentation = documentation.to_string();
+        self
+    }
+
+    /// Set field's annotation, e.g. `#[serde(default)]`.
+    pub fn annotation(&mut self, annotation: impl ToString) -> &mut Self {
+        self.annotation.push(annotation.to_string());
+        self
+    }
+
     /// Set the visibility of the field
-    pub fn vis(&mut self, visibility: impl ToString) -> &mut Self {
-        self.visibility = Some(visibility.to_string());
+    pub fn vis(&mut self, visibility: impl Into<Visibility>) -> &mut Self {
+        self.visibility = visibility.into().into_modifier();
         self
     }
 }
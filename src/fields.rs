@@ -1,19 +1,65 @@
 use std::fmt::{self, Write};
 
-use crate::field::Field;
+use crate::field::{Field, TupleField};
 use crate::formatter::Formatter;
 
 use crate::r#type::Type;
 
 /// Defines a set of fields.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Fields {
+    /// No fields, e.g. a unit struct.
     Empty,
-    Tuple(Vec<(Option<String> /* visibility */, Type)>),
+    /// Unnamed fields, e.g. a tuple struct.
+    Tuple(Vec<TupleField>),
+    /// Named fields.
     Named(Vec<Field>),
 }
 
 impl Fields {
+    /// Returns `true` if the fields are named.
+    pub fn is_named(&self) -> bool {
+        matches!(*self, Fields::Named(..))
+    }
+
+    /// Returns `true` if the fields are tuple fields.
+    pub fn is_tuple(&self) -> bool {
+        matches!(*self, Fields::Tuple(..))
+    }
+
+    /// Returns `true` if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        match *self {
+            Fields::Empty => 0,
+            Fields::Tuple(ref tys) => tys.len(),
+            Fields::Named(ref fields) => fields.len(),
+        }
+    }
+
+    /// Returns an iterator over the types of the fields, regardless of kind.
+    pub fn iter_types(&self) -> impl Iterator<Item = &Type> {
+        let (tuple, named) = match *self {
+            Fields::Empty => (&[][..], &[][..]),
+            Fields::Tuple(ref tys) => (&tys[..], &[][..]),
+            Fields::Named(ref fields) => (&[][..], &fields[..]),
+        };
+
+        tuple
+            .iter()
+            .map(|field| &field.ty)
+            .chain(named.iter().map(|field| &field.ty))
+    }
+
+    /// Push a named field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields are tuple fields.
     pub fn push_named(&mut self, field: Field) -> &mut Self {
         match *self {
             Fields::Empty => {
@@ -28,6 +74,7 @@ impl Fields {
         self
     }
 
+    /// Add a named field.
     pub fn named<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
@@ -39,9 +86,11 @@ impl Fields {
             annotation: Vec::new(),
             value: String::new(),
             visibility: None,
+            default_expr: None,
         })
     }
 
+    /// Create a named field, returning a mutable reference to it.
     pub fn new_named<T>(&mut self, name: impl ToString, ty: T) -> &mut Field
     where
         T: Into<Type>,
@@ -54,16 +103,18 @@ impl Fields {
         }
     }
 
-    pub fn tuple<T>(&mut self, vis: Option<String>, ty: T) -> &mut Self
-    where
-        T: Into<Type>,
-    {
+    /// Push a tuple field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields are named.
+    pub fn push_tuple(&mut self, field: TupleField) -> &mut Self {
         match *self {
             Fields::Empty => {
-                *self = Fields::Tuple(vec![(vis, ty.into())]);
+                *self = Fields::Tuple(vec![field]);
             }
             Fields::Tuple(ref mut fields) => {
-                fields.push((vis, ty.into()));
+                fields.push(field);
             }
             _ => panic!("field list is tuple"),
         }
@@ -71,21 +122,66 @@ impl Fields {
         self
     }
 
+    /// Add a tuple field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields are named.
+    pub fn tuple<T>(&mut self, vis: Option<String>, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        let mut field = TupleField::new(ty);
+        field.visibility = vis;
+        self.push_tuple(field)
+    }
+
+    /// Create a tuple field, returning a mutable reference to it.
+    pub fn new_tuple<T>(&mut self, ty: T) -> &mut TupleField
+    where
+        T: Into<Type>,
+    {
+        self.push_tuple(TupleField::new(ty));
+        if let Fields::Tuple(ref mut fields) = *self {
+            fields.last_mut().unwrap()
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Formats the fields using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_unterminated(fmt)?;
+
+        if let Fields::Named(..) = *self {
+            writeln!(fmt)?;
+        }
+
+        Ok(())
+    }
+
+    /// Formats the fields without a newline after the closing brace of named
+    /// fields, e.g. for an enum variant followed by a comma.
+    pub(crate) fn fmt_unterminated(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         match *self {
             Fields::Named(ref fields) => {
                 assert!(!fields.is_empty());
 
-                fmt.block(|fmt| {
+                if !fmt.is_start_of_line() {
+                    write!(fmt, " ")?;
+                }
+                writeln!(fmt, "{{")?;
+
+                fmt.indent(|fmt| {
                     for f in fields {
                         if !f.documentation.is_empty() {
                             for doc in f.documentation.lines() {
-                                write!(fmt, "/// {}\n", doc)?;
+                                writeln!(fmt, "/// {}", doc)?;
                             }
                         }
                         if !f.annotation.is_empty() {
                             for ann in &f.annotation {
-                                write!(fmt, "{}\n", ann)?;
+                                writeln!(fmt, "{}", ann)?;
                             }
                         }
                         if let Some(ref visibility) = f.visibility {
@@ -93,27 +189,59 @@ impl Fields {
                         }
                         write!(fmt, "{}: ", f.name)?;
                         f.ty.fmt(fmt)?;
-                        write!(fmt, ",\n")?;
+                        writeln!(fmt, ",")?;
                     }
 
-                    Ok(())
+                    Ok::<_, fmt::Error>(())
                 })?;
+
+                write!(fmt, "}}")?;
             }
             Fields::Tuple(ref tys) => {
                 assert!(!tys.is_empty());
-                write!(fmt, "(")?;
 
-                for (i, ty) in tys.iter().enumerate() {
-                    if i != 0 {
-                        write!(fmt, ", ")?;
-                    }
-                    if let Some(vis) = ty.0.as_ref() {
-                        write!(fmt, "{} ", vis)?;
+                // doc comments need their own line, so documented fields are
+                // formatted one per line
+                if tys.iter().any(|f| !f.documentation.is_empty()) {
+                    writeln!(fmt, "(")?;
+
+                    fmt.indent(|fmt| {
+                        for f in tys {
+                            for doc in f.documentation.lines() {
+                                writeln!(fmt, "/// {}", doc)?;
+                            }
+                            for ann in &f.annotation {
+                                writeln!(fmt, "{}", ann)?;
+                            }
+                            if let Some(ref visibility) = f.visibility {
+                                write!(fmt, "{} ", visibility)?;
+                            }
+                            f.ty.fmt(fmt)?;
+                            writeln!(fmt, ",")?;
+                        }
+
+                        Ok::<_, fmt::Error>(())
+                    })?;
+
+                    write!(fmt, ")")?;
+                } else {
+                    write!(fmt, "(")?;
+
+                    for (i, f) in tys.iter().enumerate() {
+                        if i != 0 {
+                            write!(fmt, ", ")?;
+                        }
+                        for ann in &f.annotation {
+                            write!(fmt, "{} ", ann)?;
+                        }
+                        if let Some(ref visibility) = f.visibility {
+                            write!(fmt, "{} ", visibility)?;
+                        }
+                        f.ty.fmt(fmt)?;
                     }
-                    ty.1.fmt(fmt)?;
-                }
 
-                write!(fmt, ")")?;
+                    write!(fmt, ")")?;
+                }
             }
             Fields::Empty => {}
         }
@@ -143,3 +271,38 @@ fn parse_generic() {
         assert_eq!(ret, "(pub(crate) Vec<u8>, pub Vec<u16>)");
     }
 }
+
+#[test]
+fn introspect_named() {
+    let mut fields = Fields::Empty;
+    fields.named("one", "u8").named("two", "Vec<u16>");
+
+    assert!(fields.is_named());
+    assert!(!fields.is_tuple());
+    assert!(!fields.is_empty());
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields.iter_types().map(|ty| ty.name().as_str()).collect::<Vec<&str>>(), ["u8", "Vec"]);
+}
+
+#[test]
+fn introspect_tuple() {
+    let mut fields = Fields::Empty;
+    fields.tuple(None, "u8").tuple(Some("pub".to_string()), "String").tuple(None, "bool");
+
+    assert!(!fields.is_named());
+    assert!(fields.is_tuple());
+    assert!(!fields.is_empty());
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields.iter_types().map(|ty| ty.name().as_str()).collect::<Vec<&str>>(), ["u8", "String", "bool"]);
+}
+
+#[test]
+fn introspect_empty() {
+    let fields = Fields::Empty;
+
+    assert!(!fields.is_named());
+    assert!(!fields.is_tuple());
+    assert!(fields.is_empty());
+    assert_eq!(fields.len(), 0);
+    assert_eq!(fields.iter_types().count(), 0);
+}
@@ -1,31 +1,125 @@
+use std::collections::HashMap;
 use std::fmt::{self, Write};
 
 use crate::bound::Bound;
 
 use crate::r#type::Type;
 
-const DEFAULT_INDENT: usize = 4;
+/// The indentation of a nested level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// Indent with the given number of spaces per level.
+    Spaces(usize),
+    /// Indent with one tab per level.
+    Tabs,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+/// Options controlling how a [`Scope`](crate::Scope) is formatted.
+///
+/// New options may be added, construct it with [`FmtOptions::new`] and the
+/// setters:
+///
+/// ```
+/// use codegen::{FmtOptions, Indent, Scope};
+///
+/// let mut options = FmtOptions::new();
+/// options.indent(Indent::Tabs).max_width(100);
+///
+/// let mut scope = Scope::new();
+/// scope.set_fmt_options(options);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FmtOptions {
+    /// The indentation of nested blocks, 4 spaces by default.
+    pub indent: Indent,
+
+    /// The width in columns beyond which derive lists and `where` bounds are
+    /// wrapped, one entry per line. Unlimited by default.
+    pub max_width: usize,
+}
+
+impl FmtOptions {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        FmtOptions::default()
+    }
+
+    /// Set the indentation of nested blocks.
+    pub fn indent(&mut self, indent: Indent) -> &mut Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set the width in columns beyond which derive lists and `where` bounds
+    /// are wrapped.
+    pub fn max_width(&mut self, max_width: usize) -> &mut Self {
+        self.max_width = max_width;
+        self
+    }
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        FmtOptions {
+            indent: Indent::default(),
+            max_width: usize::MAX,
+        }
+    }
+}
 
 /// Configures how a scope is formatted.
-#[derive(Debug)]
 pub struct Formatter<'a> {
     /// Write destination
-    dst: &'a mut String,
+    dst: &'a mut dyn fmt::Write,
+
+    /// Whether the destination is empty or ends with a newline
+    start_of_line: bool,
+
+    /// Indentation level to start a new line with.
+    level: usize,
+
+    /// Indentation per level
+    indent: Indent,
 
-    /// Number of spaces to start a new line with.
-    spaces: usize,
+    /// Width in columns beyond which lists are wrapped
+    max_width: usize,
 
-    /// Number of spaces per indentiation
-    indent: usize,
+    /// Names used to render type paths, keyed by the full path
+    type_names: HashMap<String, String>,
 }
 
 impl<'a> Formatter<'a> {
-    /// Return a new formatter that writes to the given string.
-    pub fn new(dst: &'a mut String) -> Self {
+    /// Return a new formatter that writes to the given destination, e.g. a
+    /// `String`.
+    ///
+    /// The destination is assumed to be empty or to end with a newline.
+    pub fn new(dst: &'a mut dyn fmt::Write) -> Self {
+        Formatter::with_options(dst, &FmtOptions::default())
+    }
+
+    /// Return a new formatter that writes to the given destination, indenting
+    /// nested blocks with `indent`.
+    pub fn with_indent(dst: &'a mut dyn fmt::Write, indent: Indent) -> Self {
+        Formatter::with_options(dst, &FmtOptions { indent, ..FmtOptions::default() })
+    }
+
+    /// Return a new formatter that writes to the given destination using the
+    /// given options.
+    pub fn with_options(dst: &'a mut dyn fmt::Write, options: &FmtOptions) -> Self {
         Formatter {
             dst,
-            spaces: 0,
-            indent: DEFAULT_INDENT,
+            start_of_line: true,
+            level: 0,
+            indent: options.indent,
+            max_width: options.max_width,
+            type_names: HashMap::new(),
         }
     }
 
@@ -38,9 +132,9 @@ impl<'a> Formatter<'a> {
             write!(self, " ")?;
         }
 
-        write!(self, "{{\n")?;
+        writeln!(self, "{{")?;
         self.indent(f)?;
-        write!(self, "}}\n")?;
+        writeln!(self, "}}")?;
         Ok(())
     }
 
@@ -49,21 +143,80 @@ impl<'a> Formatter<'a> {
     where
         F: FnOnce(&mut Self) -> R,
     {
-        self.spaces += self.indent;
+        self.level += 1;
         let ret = f(self);
-        self.spaces -= self.indent;
+        self.level -= 1;
         ret
     }
 
     /// Check if current destination is the start of a new line.
     pub fn is_start_of_line(&self) -> bool {
-        self.dst.is_empty() || self.dst.as_bytes().last() == Some(&b'\n')
+        self.start_of_line
+    }
+
+    /// Call the given function with types whose full path is a key of
+    /// `names` rendered as the associated name instead.
+    pub(crate) fn with_type_names<F, R>(&mut self, names: HashMap<String, String>, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let prev = std::mem::replace(&mut self.type_names, names);
+        let ret = f(self);
+        self.type_names = prev;
+        ret
+    }
+
+    /// Returns `true` if a line of `len` columns, starting at the current
+    /// indentation, is wider than the maximum width. Tabs count as 4 columns.
+    pub(crate) fn exceeds_max_width(&self, len: usize) -> bool {
+        let width = match self.indent {
+            Indent::Spaces(n) => n,
+            Indent::Tabs => 4,
+        };
+        self.level * width + len > self.max_width
+    }
+
+    /// Returns the output of `f` as a string, rendering types the same way as
+    /// this formatter.
+    pub(crate) fn render<F>(&self, f: F) -> Result<String, fmt::Error>
+    where
+        F: FnOnce(&mut Formatter<'_>) -> fmt::Result,
+    {
+        let mut ret = String::new();
+        let mut fmt = Formatter::new(&mut ret);
+        fmt.type_names = self.type_names.clone();
+        f(&mut fmt)?;
+        Ok(ret)
     }
 
-    fn push_spaces(&mut self) {
-        for _ in 0..self.spaces {
-            self.dst.push_str(" ");
+    /// Returns the name a type with the given path is rendered as.
+    pub(crate) fn type_name<'n>(&'n self, path: &'n str) -> &'n str {
+        self.type_names.get(path).map_or(path, String::as_str)
+    }
+
+    fn push_indent(&mut self) -> fmt::Result {
+        let (c, width) = match self.indent {
+            Indent::Spaces(n) => (' ', n),
+            Indent::Tabs => ('\t', 1),
+        };
+
+        for _ in 0..self.level * width {
+            self.dst.write_char(c)?;
         }
+
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for Formatter<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Formatter")
+            .field("start_of_line", &self.start_of_line)
+            .field("level", &self.level)
+            .field("indent", &self.indent)
+            .field("max_width", &self.max_width)
+            .field("type_names", &self.type_names)
+            .finish()
     }
 }
 
@@ -74,7 +227,7 @@ impl<'a> fmt::Write for Formatter<'a> {
 
         for line in s.lines() {
             if !first {
-                self.dst.push_str("\n");
+                self.dst.write_char('\n')?;
             }
 
             first = false;
@@ -82,23 +235,112 @@ impl<'a> fmt::Write for Formatter<'a> {
             let do_indent = should_indent && !line.is_empty() && line.as_bytes()[0] != b'\n';
 
             if do_indent {
-                self.push_spaces();
+                self.push_indent()?;
             }
 
             // If this loops again, then we just wrote a new line
             should_indent = true;
 
-            self.dst.push_str(line);
+            self.dst.write_str(line)?;
         }
 
         if s.as_bytes().last() == Some(&b'\n') {
-            self.dst.push_str("\n");
+            self.dst.write_char('\n')?;
+        }
+
+        if !s.is_empty() {
+            self.start_of_line = s.ends_with('\n');
         }
 
         Ok(())
     }
 }
 
+/// Configures how string literal values spanning multiple lines are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MultilineStr {
+    /// Escape the newlines, producing a single line literal, e.g. `"a\nb"`.
+    #[default]
+    Escape,
+
+    /// Split the literal at its newlines and join the parts with `concat!`,
+    /// e.g. `concat!("a\n", "b")`.
+    Concat,
+}
+
+/// Format a value expression, e.g. the value of a constant.
+///
+/// String literals containing newlines are rendered according to `style`,
+/// all other values are written verbatim. Newlines following an unescaped
+/// `\` are line continuations and are kept as they are.
+pub fn fmt_value(value: &str, style: MultilineStr, fmt: &mut Formatter<'_>) -> fmt::Result {
+    let is_str_literal = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+
+    if !is_str_literal || !value.contains('\n') {
+        return write!(fmt, "{}", value);
+    }
+
+    let mut lines = split_str_lines(&value[1..value.len() - 1]);
+    if lines.len() == 1 {
+        // only line continuations
+        return write!(fmt, "{}", value);
+    }
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+
+    match style {
+        MultilineStr::Escape => write!(fmt, "\"{}\"", lines.concat()),
+        MultilineStr::Concat => {
+            write!(fmt, "concat!(")?;
+
+            for (i, line) in lines.iter().enumerate() {
+                if i != 0 {
+                    write!(fmt, ", ")?;
+                }
+                write!(fmt, "\"{}\"", line)?;
+            }
+
+            write!(fmt, ")")
+        }
+    }
+}
+
+/// Splits the content of a string literal after each newline, escaping the
+/// newlines and carriage returns, e.g. `a<LF>b` becomes `a\n` and `b`.
+///
+/// Line continuations, i.e. newlines following an unescaped `\`, are kept.
+fn split_str_lines(content: &str) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut escaped = false;
+
+    for ch in content.chars() {
+        let line = lines.last_mut().unwrap();
+        match ch {
+            '\n' if escaped => {
+                line.push(ch);
+                escaped = false;
+            }
+            '\r' if escaped => line.push(ch),
+            '\n' => {
+                line.push_str("\\n");
+                lines.push(String::new());
+            }
+            '\r' => line.push_str("\\r"),
+            '\\' => {
+                line.push(ch);
+                escaped = !escaped;
+            }
+            _ => {
+                line.push(ch);
+                escaped = false;
+            }
+        }
+    }
+
+    lines
+}
+
 /// Format generics.
 pub fn fmt_generics(generics: &[String], fmt: &mut Formatter<'_>) -> fmt::Result {
     if !generics.is_empty() {
@@ -119,18 +361,53 @@ pub fn fmt_generics(generics: &[String], fmt: &mut Formatter<'_>) -> fmt::Result
 
 /// Format generic bounds.
 pub fn fmt_bounds(bounds: &[Bound], fmt: &mut Formatter<'_>) -> fmt::Result {
+    fmt_bounds_ending(bounds, ",", fmt)
+}
+
+/// Format generic bounds, sorted by the name of the bounded type.
+///
+/// Bounds sharing a name keep the order in which they were added.
+pub fn fmt_sorted_bounds(bounds: &[Bound], fmt: &mut Formatter<'_>) -> fmt::Result {
+    let mut sorted = bounds.to_vec();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+    fmt_bounds(&sorted, fmt)
+}
+
+/// Format generic bounds, terminating the last predicate with `end`
+/// instead of a comma, e.g. `;` for tuple structs.
+pub(crate) fn fmt_bounds_ending(bounds: &[Bound], end: &str, fmt: &mut Formatter<'_>) -> fmt::Result {
     if !bounds.is_empty() {
-        write!(fmt, "\n")?;
+        writeln!(fmt)?;
+
+        for (i, bound) in bounds.iter().enumerate() {
+            let name = match bound.lhs {
+                Some(ref ty) => fmt.render(|fmt| ty.fmt(fmt))?,
+                None => bound.name.clone(),
+            };
 
-        // Write first bound
-        write!(fmt, "where {}: ", bounds[0].name)?;
-        fmt_bound_rhs(&bounds[0].bound, fmt)?;
-        write!(fmt, ",\n")?;
+            if i == 0 {
+                write!(fmt, "where {}: ", name)?;
+            } else {
+                write!(fmt, "      {}: ", name)?;
+            }
 
-        for bound in &bounds[1..] {
-            write!(fmt, "      {}: ", bound.name)?;
-            fmt_bound_rhs(&bound.bound, fmt)?;
-            write!(fmt, ",\n")?;
+            let rhs = fmt.render(|fmt| fmt_bound_rhs(&bound.bound, fmt))?;
+            let end = if i + 1 == bounds.len() { end } else { "," };
+
+            // wrap long bounds, aligning each `+` under the colon
+            let len = "where ".len() + name.len() + ": ".len() + rhs.len() + end.len();
+            if bound.bound.len() > 1 && fmt.exceeds_max_width(len) {
+                let pad = " ".repeat(6 + name.len());
+                for (j, ty) in bound.bound.iter().enumerate() {
+                    if j != 0 {
+                        write!(fmt, "\n{}+ ", pad)?;
+                    }
+                    ty.fmt(fmt)?;
+                }
+            } else {
+                write!(fmt, "{}", rhs)?;
+            }
+            writeln!(fmt, "{}", end)?;
         }
     }
 
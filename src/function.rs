@@ -2,16 +2,28 @@ use std::fmt::{self, Write};
 
 use crate::block::Block;
 use crate::body::Body;
-use crate::bound::Bound;
+use crate::bound::{push_bound, Bound};
 use crate::docs::Docs;
 use crate::field::Field;
 use crate::formatter::Formatter;
-use crate::formatter::{fmt_bounds, fmt_generics};
-
-use crate::r#type::Type;
+use crate::formatter::{fmt_bounds, fmt_generics, fmt_sorted_bounds};
+use crate::visibility::Visibility;
+
+use crate::r#type::{lifetime_name, Type};
+
+/// The flavour of an `#[inline]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineKind {
+    /// `#[inline]`
+    Hint,
+    /// `#[inline(always)]`
+    Always,
+    /// `#[inline(never)]`
+    Never,
+}
 
 /// Defines a function.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Function {
     /// Name of the function
     name: String,
@@ -25,12 +37,21 @@ pub struct Function {
     /// Function visibility
     vis: Option<String>,
 
+    /// Function lifetimes
+    lifetimes: Vec<String>,
+
     /// Function generics
     generics: Vec<String>,
 
+    /// Function const generics, e.g. `const N: usize`
+    const_generics: Vec<(String, Type)>,
+
     /// If the function takes `&self` or `&mut self`
     arg_self: Option<String>,
 
+    /// The explicit type of a `self` argument, e.g. `Pin<&mut Self>`
+    arg_self_ty: Option<Type>,
+
     /// Function arguments
     args: Vec<Field>,
 
@@ -40,17 +61,38 @@ pub struct Function {
     /// Where bounds
     bounds: Vec<Bound>,
 
+    /// Whether the `where` bounds are sorted by name when formatting
+    sort_bounds: bool,
+
     /// Body contents
     pub body: Option<Vec<Body>>,
 
+    /// Whether the function is marked `#[no_mangle]`
+    no_mangle: bool,
+
+    /// The `#[inline]` attribute of the function
+    inline: Option<InlineKind>,
+
     /// Function attributes, e.g., `#[no_mangle]`.
     attributes: Vec<String>,
 
+    /// `cfg` attributes to conditionally compile the function
+    cfg: Vec<String>,
+
+    /// Whether the function is marked `#[rustfmt::skip]`
+    skip_rustfmt: bool,
+
     /// Function `extern` ABI
     extern_abi: Option<String>,
 
+    /// Whether or not this function is `const` or not
+    r#const: bool,
+
     /// Whether or not this function is `async` or not
     r#async: bool,
+
+    /// Whether or not this function is `unsafe` or not
+    r#unsafe: bool,
 }
 
 impl Function {
@@ -61,18 +103,58 @@ impl Function {
             docs: None,
             allow: None,
             vis: None,
+            lifetimes: vec![],
             generics: vec![],
+            const_generics: vec![],
             arg_self: None,
+            arg_self_ty: None,
             args: vec![],
             ret: None,
             bounds: vec![],
+            sort_bounds: false,
             body: Some(vec![]),
+            no_mangle: false,
+            inline: None,
             attributes: vec![],
+            cfg: vec![],
+            skip_rustfmt: false,
             extern_abi: None,
+            r#const: false,
             r#async: false,
+            r#unsafe: false,
         }
     }
 
+    /// Return a new builder setter for the given field.
+    ///
+    /// The generated function is named `with_<field>`, takes `mut self`,
+    /// assigns the argument to the field and returns `Self`:
+    ///
+    /// ```
+    /// use codegen::Function;
+    ///
+    /// // #[must_use]
+    /// // pub fn with_name(mut self, name: String) -> Self { .. }
+    /// let setter = Function::builder_setter("name", "String");
+    /// ```
+    pub fn builder_setter<T>(field: impl ToString, ty: T) -> Self
+    where
+        T: Into<Type>,
+    {
+        let field = field.to_string();
+        let mut func = Function::new(format!("with_{}", field));
+        func.arg_self = Some("mut self".to_string());
+
+        func.attr("must_use")
+            .vis("pub")
+            .arg(&field, ty)
+            .ret("Self")
+            .line(format!("self.{field} = {field};", field = field))
+            .line("self");
+
+        func
+    }
+
     /// Returns the name of the function
     pub fn name(&self) -> &String {
         &self.name
@@ -84,6 +166,24 @@ impl Function {
         self
     }
 
+    /// Add a runnable example to the function documentation.
+    ///
+    /// The code is embedded in a fenced `rust` block, separated from any
+    /// existing documentation by a blank line.
+    pub fn doc_example(&mut self, code: impl ToString) -> &mut Self {
+        let example = format!("```rust\n{}\n```", code.to_string().trim_end());
+
+        match self.docs {
+            Some(ref mut docs) => {
+                docs.append(format!("\n{}", example));
+            }
+            None => {
+                self.docs = Some(Docs::new(example));
+            }
+        }
+        self
+    }
+
     /// Specify lint attribute to supress a warning or error.
     pub fn allow(&mut self, allow: impl ToString) -> &mut Self {
         self.allow = Some(allow.to_string());
@@ -91,8 +191,25 @@ impl Function {
     }
 
     /// Set the function visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.vis = Some(vis.to_string());
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.vis = vis.into().into_modifier();
+        self
+    }
+
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.vis.get_or_insert_with(|| vis.to_string());
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.vis.is_some()
+    }
+
+    /// Set whether this function is const or not
+    pub fn set_const(&mut self, r#const: bool) -> &mut Self {
+        self.r#const = r#const;
         self
     }
 
@@ -102,27 +219,76 @@ impl Function {
         self
     }
 
+    /// Set whether this function is unsafe or not
+    pub fn set_unsafe(&mut self, r#unsafe: bool) -> &mut Self {
+        self.r#unsafe = r#unsafe;
+        self
+    }
+
+    /// Add a lifetime to the function, e.g. `'a`.
+    ///
+    /// Lifetimes are emitted before the other generics.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        let name = lifetime_name(name);
+        if !self.lifetimes.contains(&name) {
+            self.lifetimes.push(name);
+        }
+        self
+    }
+
     /// Add a generic to the function.
     pub fn generic(&mut self, name: impl Into<String>) -> &mut Self {
         self.generics.push(name.into());
         self
     }
 
+    /// Add a const generic to the function, e.g. `const N: usize`.
+    ///
+    /// Const generics are emitted after the type generics.
+    pub fn const_generic<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.const_generics.push((name.to_string(), ty.into()));
+        self
+    }
+
     /// Add `self` as a function argument.
     pub fn arg_self(&mut self) -> &mut Self {
         self.arg_self = Some("self".to_string());
+        self.arg_self_ty = None;
         self
     }
 
     /// Add `&self` as a function argument.
     pub fn arg_ref_self(&mut self) -> &mut Self {
         self.arg_self = Some("&self".to_string());
+        self.arg_self_ty = None;
+        self
+    }
+
+    /// Add `&'lifetime self` as a function argument, e.g. `&'a self`.
+    pub fn arg_ref_self_with_lifetime(&mut self, lifetime: &str) -> &mut Self {
+        self.arg_self = Some(format!("&{} self", lifetime));
+        self.arg_self_ty = None;
         self
     }
 
     /// Add `&mut self` as a function argument.
     pub fn arg_mut_self(&mut self) -> &mut Self {
         self.arg_self = Some("&mut self".to_string());
+        self.arg_self_ty = None;
+        self
+    }
+
+    /// Add `self` with an explicit type as a function argument, e.g.
+    /// `self: Pin<&mut Self>` or `self: Box<Self>`.
+    pub fn arg_self_ty<T>(&mut self, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.arg_self = Some("self".to_string());
+        self.arg_self_ty = Some(ty.into());
         self
     }
 
@@ -131,21 +297,29 @@ impl Function {
     where
         T: Into<Type>,
     {
-        self.args.push(Field {
-            name: name.to_string(),
-            ty: ty.into(),
-            // While a `Field` is used here, both `documentation`, `visibility`
-            // and `annotation` does not make sense for function arguments.
-            // Simply use empty strings.
-            documentation: String::new(),
-            annotation: Vec::new(),
-            value: String::new(),
-            visibility: None,
-        });
+        self.push_arg(Field::new(name, ty))
+    }
 
+    /// Push a function argument.
+    ///
+    /// Annotations of the field are emitted inline before the argument, e.g.
+    /// `#[allow(unused)] x: u32`. Its documentation and visibility do not
+    /// make sense for function arguments and are ignored.
+    pub fn push_arg(&mut self, arg: Field) -> &mut Self {
+        self.args.push(arg);
         self
     }
 
+    /// Push a new function argument, returning a mutable reference to it, e.g.
+    /// to add annotations.
+    pub fn new_arg<T>(&mut self, name: impl ToString, ty: T) -> &mut Field
+    where
+        T: Into<Type>,
+    {
+        self.push_arg(Field::new(name, ty));
+        self.args.last_mut().unwrap()
+    }
+
     /// Set the function return type.
     pub fn ret<T>(&mut self, ty: T) -> &mut Self
     where
@@ -156,18 +330,28 @@ impl Function {
     }
 
     /// Add a `where` bound to the function.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
     {
-        self.bounds.push(Bound {
-            name: name.to_string(),
-            bound: vec![ty.into()],
-        });
+        push_bound(&mut self.bounds, name.to_string(), ty.into());
+        self
+    }
+
+    /// Sort the `where` clause predicates by name when formatting the function.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.sort_bounds = sort;
         self
     }
 
     /// Push a line to the function implementation.
+    ///
+    /// A multi-line string is split at its newlines and each line is indented
+    /// to the level of the body, so a snippet should not be indented itself.
+    /// See [`dedent`](crate::dedent) for snippets written as indented string
+    /// literals.
     pub fn line<T>(&mut self, line: T) -> &mut Self
     where
         T: ToString,
@@ -194,6 +378,42 @@ impl Function {
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the function, e.g.
+    /// `feature = "std"`.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
+    /// Mark the function `#[no_mangle]`.
+    ///
+    /// ```
+    /// use codegen::{Function, InlineKind};
+    ///
+    /// // #[no_mangle]
+    /// // #[inline(never)]
+    /// // pub extern "C" fn ffi_export() { .. }
+    /// let mut func = Function::new("ffi_export");
+    /// func.no_mangle().inline(InlineKind::Never).vis("pub").extern_abi("C");
+    /// ```
+    pub fn no_mangle(&mut self) -> &mut Self {
+        self.no_mangle = true;
+        self
+    }
+
+    /// Emit `#[rustfmt::skip]` above the function, e.g. to keep a
+    /// hand-aligned layout.
+    pub fn skip_rustfmt(&mut self) -> &mut Self {
+        self.skip_rustfmt = true;
+        self
+    }
+
+    /// Set the `#[inline]` attribute of the function.
+    pub fn inline(&mut self, kind: InlineKind) -> &mut Self {
+        self.inline = Some(kind);
+        self
+    }
+
     /// Specify an `extern` ABI for the function.
     /// ```
     /// use codegen::Function;
@@ -215,21 +435,84 @@ impl Function {
         self
     }
 
+    /// Remove the function body, so the function is formatted as a bare
+    /// signature ending in `;`, e.g. a required trait method.
+    ///
+    /// Pushing a line or block afterwards adds a new body.
+    pub fn remove_body(&mut self) -> &mut Self {
+        self.body = None;
+        self
+    }
+
+    /// Push an `unsafe { .. }` block, filled in by `f`, to the function
+    /// implementation.
+    ///
+    /// ```
+    /// use codegen::Function;
+    ///
+    /// // fn read(ptr: *const u8) -> u8 {
+    /// //     unsafe {
+    /// //         *ptr
+    /// //     }
+    /// // }
+    /// let mut func = Function::new("read");
+    /// func.arg("ptr", "*const u8").ret("u8").unsafe_block(|b| {
+    ///     b.line("*ptr");
+    /// });
+    /// ```
+    pub fn unsafe_block<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Block),
+    {
+        let mut block = Block::new("unsafe");
+        f(&mut block);
+        self.push_block(block)
+    }
+
     /// Formats the function using the given formatter.
     pub fn fmt(&self, is_trait: bool, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with(!is_trait, !is_trait, fmt)
+    }
+
+    /// Formats the function as a declaration inside an `extern` block.
+    pub(crate) fn fmt_foreign(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        assert!(self.body.is_none(), "foreign fns cannot have a body");
+        self.fmt_with(true, false, fmt)
+    }
+
+    fn fmt_with(&self, allow_vis: bool, require_body: bool, fmt: &mut Formatter<'_>) -> fmt::Result {
         if let Some(ref docs) = self.docs {
             docs.fmt(fmt)?;
         }
 
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
+        }
+
         if let Some(ref allow) = self.allow {
-            write!(fmt, "#[allow({})]\n", allow)?;
+            writeln!(fmt, "#[allow({})]", allow)?;
+        }
+
+        if self.no_mangle {
+            writeln!(fmt, "#[no_mangle]")?;
+        }
+
+        match self.inline {
+            Some(InlineKind::Hint) => writeln!(fmt, "#[inline]")?,
+            Some(InlineKind::Always) => writeln!(fmt, "#[inline(always)]")?,
+            Some(InlineKind::Never) => writeln!(fmt, "#[inline(never)]")?,
+            None => {}
         }
 
         for attr in self.attributes.iter() {
-            write!(fmt, "#[{}]\n", attr)?;
+            writeln!(fmt, "#[{}]", attr)?;
+        }
+
+        if self.skip_rustfmt {
+            writeln!(fmt, "#[rustfmt::skip]")?;
         }
 
-        if is_trait {
+        if !allow_vis {
             assert!(
                 self.vis.is_none(),
                 "trait fns do not have visibility modifiers"
@@ -240,28 +523,49 @@ impl Function {
             write!(fmt, "{} ", vis)?;
         }
 
-        if let Some(ref extern_abi) = self.extern_abi {
-            write!(fmt, "extern \"{extern_abi}\" ", extern_abi = extern_abi)?;
+        assert!(
+            !(self.r#const && self.r#async),
+            "functions cannot be both `const` and `async`"
+        );
+
+        // qualifiers are emitted in the order required by Rust
+        if self.r#const {
+            write!(fmt, "const ")?;
         }
 
         if self.r#async {
             write!(fmt, "async ")?;
         }
 
+        if self.r#unsafe {
+            write!(fmt, "unsafe ")?;
+        }
+
+        if let Some(ref extern_abi) = self.extern_abi {
+            write!(fmt, "extern \"{extern_abi}\" ", extern_abi = extern_abi)?;
+        }
+
         write!(fmt, "fn {}", self.name)?;
-        fmt_generics(&self.generics, fmt)?;
+        self.fmt_generics(fmt)?;
 
         write!(fmt, "(")?;
 
         if let Some(ref s) = self.arg_self {
             write!(fmt, "{}", s)?;
         }
+        if let Some(ref ty) = self.arg_self_ty {
+            write!(fmt, ": ")?;
+            ty.fmt(fmt)?;
+        }
 
         for (i, arg) in self.args.iter().enumerate() {
             if i != 0 || self.arg_self.is_some() {
                 write!(fmt, ", ")?;
             }
 
+            for annotation in &arg.annotation {
+                write!(fmt, "{} ", annotation)?;
+            }
             write!(fmt, "{}: ", arg.name)?;
             arg.ty.fmt(fmt)?;
         }
@@ -273,7 +577,11 @@ impl Function {
             ret.fmt(fmt)?;
         }
 
-        fmt_bounds(&self.bounds, fmt)?;
+        if self.sort_bounds {
+            fmt_sorted_bounds(&self.bounds, fmt)?;
+        } else {
+            fmt_bounds(&self.bounds, fmt)?;
+        }
 
         match self.body {
             Some(ref body) => fmt.block(|fmt| {
@@ -284,12 +592,24 @@ impl Function {
                 Ok(())
             }),
             None => {
-                if !is_trait {
+                if require_body {
                     panic!("impl blocks must define fn bodies");
                 }
 
-                write!(fmt, ";\n")
+                writeln!(fmt, ";")
             }
         }
     }
+
+    fn fmt_generics(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        let mut generics = self.lifetimes.clone();
+        generics.extend(self.generics.iter().cloned());
+
+        for (name, ty) in &self.const_generics {
+            let ty = fmt.render(|fmt| ty.fmt(fmt))?;
+            generics.push(format!("const {}: {}", name, ty));
+        }
+
+        fmt_generics(&generics, fmt)
+    }
 }
@@ -1,18 +1,29 @@
 use std::fmt::{self, Write};
 
-use crate::bound::Bound;
+use crate::associated_type::AssociatedType;
+use crate::bound::{push_bound, push_bound_ty, Bound};
 use crate::field::Field;
-use crate::formatter::{fmt_bounds, fmt_generics, Formatter};
+use crate::formatter::{fmt_bounds, fmt_generics, fmt_sorted_bounds, fmt_value, Formatter, MultilineStr};
 use crate::function::Function;
+use crate::visibility::Visibility;
 
-use crate::r#type::Type;
+use crate::r#type::{lifetime_name, Type};
+
+/// Traits whose impls for a generic target sort by their generic parameter,
+/// keeping them next to the type converted from or to.
+///
+/// This is the default list, see [`Scope::set_conversion_traits`](crate::Scope::set_conversion_traits).
+pub const CONVERSION_TRAITS: &[&str] = &["From", "TryFrom", "Into", "TryInto", "AsRef", "AsMut"];
 
 /// Defines an impl block.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Impl {
     /// The struct being implemented
     target: Type,
 
+    /// Impl level lifetimes
+    lifetimes: Vec<String>,
+
     /// Impl level generics
     generics: Vec<String>,
 
@@ -22,14 +33,35 @@ pub struct Impl {
     /// Associated constants
     assoc_csts: Vec<Field>,
 
+    /// How multi-line string values of associated constants are rendered
+    multiline_str: MultilineStr,
+
     /// Associated types
-    assoc_tys: Vec<Field>,
+    assoc_tys: Vec<AssociatedType>,
 
     /// Bounds
     bounds: Vec<Bound>,
 
+    /// Whether the bounds are sorted by name when formatting
+    sort_bounds: bool,
+
     fns: Vec<Function>,
 
+    /// Lint attributes used to suppress warnings or errors
+    allow: Vec<String>,
+
+    /// `cfg` attributes to conditionally compile the impl block
+    cfg: Vec<String>,
+
+    /// Whether the impl block is marked `#[rustfmt::skip]`
+    skip_rustfmt: bool,
+
+    /// Whether the impl block is `unsafe`
+    r#unsafe: bool,
+
+    /// Whether the trait is implemented negatively, e.g. `impl !Send for T`
+    negative: bool,
+
     macros: Vec<String>,
 }
 
@@ -41,12 +73,20 @@ impl Impl {
     {
         Impl {
             target: target.into(),
+            lifetimes: Vec::new(),
             generics: Vec::new(),
             impl_trait: None,
             assoc_csts: Vec::new(),
+            multiline_str: MultilineStr::default(),
             assoc_tys: Vec::new(),
             bounds: Vec::new(),
+            sort_bounds: false,
             fns: Vec::new(),
+            allow: Vec::new(),
+            cfg: Vec::new(),
+            skip_rustfmt: false,
+            r#unsafe: false,
+            negative: false,
             macros: Vec::new(),
         }
     }
@@ -57,16 +97,27 @@ impl Impl {
     }
 
     /// Returns the key for sorting
+    ///
+    /// Impls for a generic target, such as `impl Display for Vec<Foo>`, sort
+    /// by the trait. Impls of a [conversion trait](CONVERSION_TRAITS) for a
+    /// generic target sort by the converted type instead, e.g.
+    /// `impl TryFrom<Foo> for Vec<u8>` sorts next to `Foo`.
     pub fn key_for_sorting(&self) -> &Type {
-        if self.target.generics().is_empty() || self.impl_trait.is_none() {
-            &self.target
-        } else {
-            let impl_type = self.impl_trait.as_ref().unwrap();
-            if impl_type.name() == "From" {
-                impl_type.generics().get(0).unwrap()
-            } else {
-                impl_type
+        self.key_for_sorting_with(CONVERSION_TRAITS)
+    }
+
+    /// Returns the key for sorting like [`Impl::key_for_sorting`], with the
+    /// given names of conversion traits.
+    pub fn key_for_sorting_with<S: AsRef<str>>(&self, conversion_traits: &[S]) -> &Type {
+        match self.impl_trait {
+            Some(ref impl_type) if !self.target.generics().is_empty() => {
+                let name = impl_type.key_for_sorting();
+                match impl_type.generics().first() {
+                    Some(converted) if conversion_traits.iter().any(|t| t.as_ref() == name) => converted,
+                    _ => impl_type,
+                }
             }
+            _ => &self.target,
         }
     }
 
@@ -74,8 +125,23 @@ impl Impl {
     /// Add a generic to the impl block.
     ///
     /// This adds the generic for the block (`impl<T>`) and not the target type.
+    /// Generics that were already added are ignored.
     pub fn generic(&mut self, name: impl ToString) -> &mut Self {
-        self.generics.push(name.to_string());
+        let name = name.to_string();
+        if !self.generics.contains(&name) {
+            self.generics.push(name);
+        }
+        self
+    }
+
+    /// Add a lifetime to the impl block, e.g. `'a`.
+    ///
+    /// Lifetimes are emitted before the other generics of the block.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        let name = lifetime_name(name);
+        if !self.lifetimes.contains(&name) {
+            self.lifetimes.push(name);
+        }
         self
     }
 
@@ -97,6 +163,42 @@ impl Impl {
         self
     }
 
+    /// Set whether the impl block is `unsafe`, e.g. `unsafe impl Send for Foo`.
+    pub fn set_unsafe(&mut self, r#unsafe: bool) -> &mut Self {
+        self.r#unsafe = r#unsafe;
+        self
+    }
+
+    /// Implement the trait negatively, e.g. `impl !Send for Foo {}`.
+    ///
+    /// A negative impl has an empty body, so formatting panics if no trait is
+    /// set or if the impl block has any items.
+    pub fn negative(&mut self) -> &mut Self {
+        self.negative = true;
+        self
+    }
+
+    /// Specify lint attribute to supress a warning or error, e.g.
+    /// `clippy::too_many_arguments`.
+    pub fn allow(&mut self, allow: impl ToString) -> &mut Self {
+        self.allow.push(allow.to_string());
+        self
+    }
+
+    /// Add a `cfg` attribute to conditionally compile the impl block, e.g.
+    /// `feature = "serde"`.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
+    /// Emit `#[rustfmt::skip]` above the impl block, e.g. to keep a
+    /// hand-aligned layout.
+    pub fn skip_rustfmt(&mut self) -> &mut Self {
+        self.skip_rustfmt = true;
+        self
+    }
+
     /// Add a macro to the impl block (e.g. `"#[async_trait]"`)
     pub fn r#macro(&mut self, r#macro: impl ToString) -> &mut Self {
         self.macros.push(r#macro.to_string());
@@ -109,49 +211,79 @@ impl Impl {
         name: impl ToString,
         ty: T,
         value: impl ToString,
-        visibility: impl ToString,
+        visibility: impl Into<Visibility>,
     ) -> &mut Self
     where
         T: Into<Type>,
     {
-        self.assoc_csts.push(Field {
-            name: name.to_string(),
-            ty: ty.into(),
-            documentation: String::new(),
-            annotation: Vec::new(),
-            value: value.to_string(),
-            visibility: Some(visibility.to_string()),
-        });
+        let mut cst = Field::new(name, ty);
+        cst.value(value).vis(visibility);
+        self.push_associate_const(cst)
+    }
+
+    /// Push an associated constant.
+    ///
+    /// The constant is initialized with the [value](Field::value) of the
+    /// field, and its documentation and annotations are emitted above it.
+    pub fn push_associate_const(&mut self, cst: Field) -> &mut Self {
+        self.assoc_csts.push(cst);
+        self
+    }
 
+    /// Set how multi-line string values of associated constants are
+    /// rendered.
+    pub fn multiline_str(&mut self, style: MultilineStr) -> &mut Self {
+        self.multiline_str = style;
         self
     }
 
     /// Set an associated type.
+    ///
+    /// Use [`new_associate_type`](Self::new_associate_type) for a generic
+    /// associated type.
     pub fn associate_type<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
     {
-        self.assoc_tys.push(Field {
-            name: name.to_string(),
-            ty: ty.into(),
-            documentation: String::new(),
-            annotation: Vec::new(),
-            value: String::new(),
-            visibility: None,
-        });
-
+        self.new_associate_type(name, ty);
         self
     }
 
+    /// Set an associated type, returning it to add generics and `where`
+    /// bounds, e.g. `type Iter<'a> = std::slice::Iter<'a, u8> where Self: 'a;`.
+    pub fn new_associate_type<T>(&mut self, name: impl ToString, ty: T) -> &mut AssociatedType
+    where
+        T: Into<Type>,
+    {
+        self.assoc_tys.push(AssociatedType::with_value(name, ty.into()));
+        self.assoc_tys.last_mut().unwrap()
+    }
+
     /// Add a `where` bound to the impl block.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
     {
-        self.bounds.push(Bound {
-            name: name.to_string(),
-            bound: vec![ty.into()],
-        });
+        push_bound(&mut self.bounds, name.to_string(), ty.into());
+        self
+    }
+
+    /// Add a `where` bound to the impl block whose left-hand side is a type,
+    /// e.g. `where T::Item: Clone`.
+    pub fn bound_ty<L, R>(&mut self, lhs: L, rhs: R) -> &mut Self
+    where
+        L: Into<Type>,
+        R: Into<Type>,
+    {
+        push_bound_ty(&mut self.bounds, lhs.into(), rhs.into());
+        self
+    }
+
+    /// Sort the `where` clause predicates by name when formatting the impl block.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.sort_bounds = sort;
         self
     }
 
@@ -169,14 +301,30 @@ impl Impl {
 
     /// Formats the impl block using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
+        }
+        for allow in self.allow.iter() {
+            writeln!(fmt, "#[allow({})]", allow)?;
+        }
+        if self.skip_rustfmt {
+            writeln!(fmt, "#[rustfmt::skip]")?;
+        }
         for m in self.macros.iter() {
-            write!(fmt, "{}\n", m)?;
+            writeln!(fmt, "{}", m)?;
+        }
+        if self.r#unsafe {
+            write!(fmt, "unsafe ")?;
         }
         write!(fmt, "impl")?;
-        fmt_generics(&self.generics[..], fmt)?;
+        let generics = self.lifetimes.iter().chain(&self.generics).cloned().collect::<Vec<_>>();
+        fmt_generics(&generics, fmt)?;
 
         if let Some(ref t) = self.impl_trait {
             write!(fmt, " ")?;
+            if self.negative {
+                write!(fmt, "!")?;
+            }
             t.fmt(fmt)?;
             write!(fmt, " for")?;
         }
@@ -184,33 +332,62 @@ impl Impl {
         write!(fmt, " ")?;
         self.target.fmt(fmt)?;
 
-        fmt_bounds(&self.bounds, fmt)?;
+        if self.sort_bounds {
+            fmt_sorted_bounds(&self.bounds, fmt)?;
+        } else {
+            fmt_bounds(&self.bounds, fmt)?;
+        }
+
+        if self.negative {
+            let mut target = String::new();
+            self.target.fmt(&mut Formatter::new(&mut target))?;
+            assert!(self.impl_trait.is_some(), "negative impl for `{}` must have a trait", target);
+            assert!(
+                self.assoc_csts.is_empty() && self.assoc_tys.is_empty() && self.fns.is_empty(),
+                "negative impl for `{}` cannot have items",
+                target
+            );
+            if !fmt.is_start_of_line() {
+                write!(fmt, " ")?;
+            }
+            return writeln!(fmt, "{{}}");
+        }
 
         fmt.block(|fmt| {
             // format associated constants
             if !self.assoc_csts.is_empty() {
                 for cst in &self.assoc_csts {
+                    for doc in cst.documentation.lines() {
+                        writeln!(fmt, "/// {}", doc)?;
+                    }
+                    for ann in &cst.annotation {
+                        writeln!(fmt, "{}", ann)?;
+                    }
                     if let Some(vis) = &cst.visibility {
                         write!(fmt, "{} ", vis)?;
                     }
                     write!(fmt, "const {}: ", cst.name)?;
                     cst.ty.fmt(fmt)?;
-                    write!(fmt, " = {};\n", cst.value)?;
+                    write!(fmt, " = ")?;
+                    fmt_value(&cst.value, self.multiline_str, fmt)?;
+                    writeln!(fmt, ";")?;
                 }
             }
 
             // format associated types
             if !self.assoc_tys.is_empty() {
+                if !self.assoc_csts.is_empty() {
+                    writeln!(fmt)?;
+                }
+
                 for ty in &self.assoc_tys {
-                    write!(fmt, "type {} = ", ty.name)?;
-                    ty.ty.fmt(fmt)?;
-                    write!(fmt, ";\n")?;
+                    ty.fmt(fmt)?;
                 }
             }
 
             for (i, func) in self.fns.iter().enumerate() {
-                if i != 0 || !self.assoc_tys.is_empty() {
-                    write!(fmt, "\n")?;
+                if i != 0 || !self.assoc_csts.is_empty() || !self.assoc_tys.is_empty() {
+                    writeln!(fmt)?;
                 }
 
                 func.fmt(false, fmt)?;
@@ -247,3 +424,22 @@ fn type_alias() {
         assert_eq!(impl_type.key_for_sorting().name(), "Bar");
     }
 }
+
+#[test]
+fn conversion_trait_sorting() {
+    for (trait_ty, key) in [
+        ("From<Bar>", "Bar"),
+        ("TryFrom<Bar>", "Bar"),
+        ("std::convert::TryFrom<Bar>", "Bar"),
+        ("AsRef<Bar>", "Bar"),
+        ("PartialEq<Bar>", "PartialEq"),
+    ] {
+        let mut impl_type = Impl::new("Vec<Foo>");
+        impl_type.impl_trait(trait_ty);
+        assert_eq!(impl_type.key_for_sorting().key_for_sorting(), key);
+    }
+
+    let mut impl_type = Impl::new("Foo");
+    impl_type.impl_trait("TryFrom<Bar>");
+    assert_eq!(impl_type.key_for_sorting().key_for_sorting(), "Foo");
+}
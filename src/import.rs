@@ -1,6 +1,9 @@
+use crate::visibility::Visibility;
+
 /// Defines an import (`use` statement).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Import {
+    #[allow(dead_code)]
     line: String,
 
     /// Function visibility
@@ -8,12 +11,18 @@ pub struct Import {
 
     /// Alias using the `as` keyword
     pub alias: Option<String>,
+
+    /// `cfg` attributes to conditionally compile the import
+    pub cfg: Vec<String>,
 }
 
 impl Import {
     /// Return a new import.
     pub fn new(path: impl ToString, ty: impl ToString, alias: Option<&str>) -> Self {
-        let base_line = format!("{}::{}", path.to_string(), ty.to_string());
+        let ty = ty.to_string();
+        assert!(ty != "*" || alias.is_none(), "glob imports cannot be renamed");
+
+        let base_line = format!("{}::{}", path.to_string(), ty);
         Import {
             line: match alias {
                 None => base_line,
@@ -21,16 +30,26 @@ impl Import {
             },
             vis: None,
             alias: alias.map(ToOwned::to_owned),
+            cfg: Vec::new(),
         }
     }
 
     /// Set the import visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.vis = Some(vis.to_string());
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.vis = vis.into().into_modifier();
         self
     }
 
-    /// Set the import visibility.
+    /// Add a `cfg` attribute to conditionally compile the import, e.g.
+    /// `feature = "serde"`.
+    ///
+    /// Imports are only grouped with imports gated by the same attributes.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
+    /// Set the import alias.
     pub fn alias(&mut self, alias: Option<&str>) -> &mut Self {
         self.alias = alias.map(ToOwned::to_owned);
         self
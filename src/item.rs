@@ -1,20 +1,158 @@
+use std::fmt::{self, Write};
+
+use crate::extern_block::ExternBlock;
+use crate::formatter::Formatter;
 use crate::function::Function;
 use crate::module::Module;
+use crate::trait_alias::TraitAlias;
+use crate::union::Union;
 
+use crate::r#const::Const;
 use crate::r#enum::Enum;
 use crate::r#impl::Impl;
 use crate::r#struct::Struct;
 use crate::r#trait::Trait;
 use crate::r#type_alias::TypeAlias;
 
-#[derive(Debug, Clone)]
+/// Defines an item of a scope.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Item {
+    /// A module definition.
     Module(Module),
+    /// A struct definition.
     Struct(Struct),
+    /// A function definition.
     Function(Function),
+    /// A trait definition.
     Trait(Trait),
+    /// An enum definition.
     Enum(Enum),
+    /// An `impl` block.
     Impl(Impl),
+    /// A raw string, included verbatim.
     Raw(String),
+    /// A type alias.
     TypeAlias(TypeAlias),
+    /// An `extern` block.
+    ExternBlock(ExternBlock),
+    /// A `const` or `static` item.
+    Const(Const),
+    /// A union definition.
+    Union(Union),
+    /// A trait alias.
+    TraitAlias(TraitAlias),
+}
+
+/// Ranks items sharing the same name when sorting a scope.
+///
+/// The declaration order of the variants is the output order, so a struct
+/// always comes before the `impl` blocks for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum KindRank {
+    ExternBlock,
+    Const,
+    TypeAlias,
+    Struct,
+    Enum,
+    Union,
+    Function,
+    Impl,
+    Module,
+    Trait,
+    TraitAlias,
+}
+
+impl Item {
+    /// Set the visibility of the item if none was set explicitly.
+    ///
+    /// Items without a visibility, i.e. `impl` blocks and raw strings, are
+    /// left untouched.
+    pub(crate) fn default_vis(&mut self, vis: &str) {
+        match *self {
+            Item::Module(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Struct(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Function(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Trait(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Enum(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::TypeAlias(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Const(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Union(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::TraitAlias(ref mut v) => {
+                v.default_vis(vis);
+            }
+            Item::Impl(_) | Item::ExternBlock(_) | Item::Raw(_) => {}
+        }
+    }
+
+    /// Returns `true` if [`Item::default_vis`] would change the item.
+    pub(crate) fn needs_default_vis(&self) -> bool {
+        match *self {
+            Item::Module(ref v) => !v.has_vis(),
+            Item::Struct(ref v) => !v.has_vis(),
+            Item::Function(ref v) => !v.has_vis(),
+            Item::Trait(ref v) => !v.has_vis(),
+            Item::Enum(ref v) => !v.has_vis(),
+            Item::TypeAlias(ref v) => !v.has_vis(),
+            Item::Const(ref v) => !v.has_vis(),
+            Item::Union(ref v) => !v.has_vis(),
+            Item::TraitAlias(ref v) => !v.has_vis(),
+            Item::Impl(_) | Item::ExternBlock(_) | Item::Raw(_) => false,
+        }
+    }
+
+    /// Returns the key used to sort the item within its scope, or `None` if
+    /// the item is not sorted.
+    ///
+    /// Impls of one of `conversion_traits` sort by the converted type.
+    pub(crate) fn sort_key<'a>(&'a self, conversion_traits: &[String]) -> Option<(&'a str, KindRank)> {
+        match *self {
+            Item::Module(ref v) => Some((&v.name, KindRank::Module)),
+            Item::Struct(ref v) => Some((v.ty().key_for_sorting(), KindRank::Struct)),
+            Item::Function(ref v) => Some((v.name(), KindRank::Function)),
+            Item::Trait(ref v) => Some((v.ty().key_for_sorting(), KindRank::Trait)),
+            Item::Enum(ref v) => Some((v.ty().key_for_sorting(), KindRank::Enum)),
+            Item::Impl(ref v) => Some((v.key_for_sorting_with(conversion_traits).key_for_sorting(), KindRank::Impl)),
+            Item::TypeAlias(ref v) => Some((v.type_def().key_for_sorting(), KindRank::TypeAlias)),
+            Item::Const(ref v) => Some((v.name(), KindRank::Const)),
+            Item::Union(ref v) => Some((v.ty().key_for_sorting(), KindRank::Union)),
+            Item::TraitAlias(ref v) => Some((v.ty().key_for_sorting(), KindRank::TraitAlias)),
+            // extern blocks have no name and come first
+            Item::ExternBlock(_) => Some(("", KindRank::ExternBlock)),
+            Item::Raw(_) => None,
+        }
+    }
+
+    /// Formats the item using the given formatter.
+    pub(crate) fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Item::Module(ref v) => v.fmt(fmt),
+            Item::Struct(ref v) => v.fmt(fmt),
+            Item::Function(ref v) => v.fmt(false, fmt),
+            Item::Trait(ref v) => v.fmt(fmt),
+            Item::Enum(ref v) => v.fmt(fmt),
+            Item::Impl(ref v) => v.fmt(fmt),
+            Item::TypeAlias(ref v) => v.fmt(fmt),
+            Item::ExternBlock(ref v) => v.fmt(fmt),
+            Item::Const(ref v) => v.fmt(fmt),
+            Item::Union(ref v) => v.fmt(fmt),
+            Item::TraitAlias(ref v) => v.fmt(fmt),
+            Item::Raw(ref v) => writeln!(fmt, "{}", v),
+        }
+    }
 }
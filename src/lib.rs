@@ -28,7 +28,9 @@ mod associated_type;
 mod block;
 mod body;
 mod bound;
+mod cfg;
 mod docs;
+mod extern_block;
 mod field;
 mod fields;
 mod formatter;
@@ -38,27 +40,40 @@ mod item;
 mod module;
 mod scope;
 mod type_def;
+mod union;
 mod variant;
+mod visibility;
 
+mod r#const;
 mod r#enum;
 mod r#impl;
 mod r#struct;
 mod r#trait;
 mod r#type;
+mod trait_alias;
 mod type_alias;
 
 pub use associated_const::*;
 pub use associated_type::*;
 pub use block::*;
+pub use cfg::*;
+pub use docs::dedent;
+pub use extern_block::*;
 pub use field::*;
+pub use fields::*;
 pub use formatter::*;
 pub use function::*;
 pub use import::*;
+pub use item::*;
+pub use trait_alias::*;
 pub use type_alias::*;
 pub use module::*;
 pub use scope::*;
+pub use union::*;
 pub use variant::*;
+pub use visibility::*;
 
+pub use r#const::*;
 pub use r#enum::*;
 pub use r#impl::*;
 pub use r#struct::*;
@@ -1,17 +1,22 @@
 use std::fmt::{self, Display, Write};
 
 use crate::docs::Docs;
+use crate::extern_block::ExternBlock;
 use crate::formatter::Formatter;
 use crate::function::Function;
 use crate::scope::Scope;
+use crate::visibility::Visibility;
 
+use crate::r#const::Const;
 use crate::r#enum::Enum;
 use crate::r#impl::Impl;
 use crate::r#struct::Struct;
 use crate::r#trait::Trait;
+use crate::r#type::Type;
+use crate::union::Union;
 
 /// Defines a module.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Module {
     /// Module name
     pub name: String,
@@ -27,6 +32,12 @@ pub struct Module {
 
     /// Module attributes, e.g., `#[allow(unused_imports)]`.
     attributes: Vec<String>,
+
+    /// `cfg` attributes to conditionally compile the module
+    cfg: Vec<String>,
+
+    /// Whether only the `mod foo;` declaration is emitted
+    declaration_only: bool,
 }
 
 impl Module {
@@ -38,6 +49,8 @@ impl Module {
             docs: None,
             scope: Scope::new(),
             attributes: Vec::new(),
+            cfg: Vec::new(),
+            declaration_only: false,
         }
     }
 
@@ -46,9 +59,57 @@ impl Module {
         &mut self.scope
     }
 
+    /// Remove all items and imports of the module, keeping the allocations
+    /// for reuse.
+    ///
+    /// The documentation of the module is kept if `keep_docs` is set.
+    pub fn clear(&mut self, keep_docs: bool) -> &mut Self {
+        if !keep_docs {
+            self.docs = None;
+        }
+        self.scope.clear(keep_docs);
+        self
+    }
+
     /// Set the module visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.vis = Some(vis.to_string());
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.vis = vis.into().into_modifier();
+        self
+    }
+
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.vis.get_or_insert_with(|| vis.to_string());
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.vis.is_some()
+    }
+
+    /// Set whether only the module declaration is emitted, e.g. `mod foo;`,
+    /// for a module defined in its own file.
+    ///
+    /// The contents of the module's scope are then not emitted. They can be
+    /// written to the module's file instead, e.g. with
+    /// `module.scope().write_to_file("src/foo.rs")`.
+    pub fn set_declaration_only(&mut self, declaration_only: bool) -> &mut Self {
+        self.declaration_only = declaration_only;
+        self
+    }
+
+    /// Set the module documentation, emitted as outer doc comments (`///`)
+    /// before the `mod` declaration.
+    pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.docs = Some(Docs::new(docs));
+        self
+    }
+
+    /// Set the inner module documentation, emitted as inner doc comments
+    /// (`//!`) as the first lines inside the module.
+    pub fn inner_doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.scope.doc(docs);
         self
     }
 
@@ -61,12 +122,26 @@ impl Module {
         self
     }
 
+    /// Import all public items of `path` into the module's scope with a glob
+    /// import, e.g. `use super::*;`.
+    pub fn import_glob(&mut self, path: impl ToString) -> &mut Self {
+        self.scope.import_glob(path);
+        self
+    }
+
     /// Add an attribute to the module.
     pub fn attr(&mut self, attribute: impl ToString) -> &mut Self {
         self.attributes.push(attribute.to_string());
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the module, e.g.
+    /// `test`.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.cfg.push(cfg.to_string());
+        self
+    }
+
     /// Push a new module definition, returning a mutable reference to it.
     ///
     /// # Panics
@@ -136,11 +211,27 @@ impl Module {
         self
     }
 
+    /// Push a new union definition, returning a mutable reference to it.
+    pub fn new_union(&mut self, name: impl ToString) -> &mut Union {
+        self.scope.new_union(name)
+    }
+
+    /// Push a union definition
+    pub fn push_union(&mut self, item: Union) -> &mut Self {
+        self.scope.push_union(item);
+        self
+    }
+
     /// Push a new function definition, returning a mutable reference to it.
     pub fn new_fn(&mut self, name: impl ToString) -> &mut Function {
         self.scope.new_fn(name)
     }
 
+    /// Push a new test function, returning a mutable reference to it.
+    pub fn new_test_fn(&mut self, name: impl ToString) -> &mut Function {
+        self.scope.new_test_fn(name)
+    }
+
     /// Push a function definition
     pub fn push_fn(&mut self, item: Function) -> &mut Self {
         self.scope.push_fn(item);
@@ -163,12 +254,43 @@ impl Module {
         self.scope.new_impl(target)
     }
 
+    /// Push a new `impl Drop` block, returning a mutable reference to its
+    /// `drop` function.
+    pub fn new_drop_impl(&mut self, target: impl ToString) -> &mut Function {
+        self.scope.new_drop_impl(target)
+    }
+
     /// Push an `impl` block.
     pub fn push_impl(&mut self, item: Impl) -> &mut Self {
         self.scope.push_impl(item);
         self
     }
 
+    /// Push a new `const` item, returning a mutable reference to it.
+    pub fn new_const<T>(&mut self, name: impl ToString, ty: T, value: impl ToString) -> &mut Const
+    where
+        T: Into<Type>,
+    {
+        self.scope.new_const(name, ty, value)
+    }
+
+    /// Push a `const` or `static` item.
+    pub fn push_const(&mut self, item: Const) -> &mut Self {
+        self.scope.push_const(item);
+        self
+    }
+
+    /// Push a new `extern` block, returning a mutable reference to it.
+    pub fn new_extern_block(&mut self, abi: impl ToString) -> &mut ExternBlock {
+        self.scope.new_extern_block(abi)
+    }
+
+    /// Push an `extern` block.
+    pub fn push_extern_block(&mut self, item: ExternBlock) -> &mut Self {
+        self.scope.push_extern_block(item);
+        self
+    }
+
     /// Push a new trait
     pub fn new_trait(&mut self, name: impl ToString) -> &mut Trait {
         self.scope.new_trait(name)
@@ -182,14 +304,31 @@ impl Module {
 
     /// Formats the module using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_default_vis(None, fmt)
+    }
+
+    /// Formats the module, using `default_vis` if no visibility was set.
+    pub(crate) fn fmt_with_default_vis(&self, default_vis: Option<&str>, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(ref docs) = self.docs {
+            docs.fmt(fmt)?;
+        }
+
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
+        }
+
         for attr in &self.attributes {
-            writeln!(fmt, "#[{}] ", attr)?;
+            writeln!(fmt, "#[{}]", attr)?;
         }
 
-        if let Some(ref vis) = self.vis {
+        if let Some(vis) = self.vis.as_deref().or(default_vis) {
             write!(fmt, "{} ", vis)?;
         }
 
+        if self.declaration_only {
+            return writeln!(fmt, "mod {};", self.name);
+        }
+
         write!(fmt, "mod {}", self.name)?;
         fmt.block(|fmt| self.scope.fmt(fmt))
     }
@@ -1,25 +1,44 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Debug, Display, Write};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
 
 use indexmap::IndexMap;
 
 use crate::docs::Docs;
-use crate::formatter::Formatter;
+use crate::extern_block::ExternBlock;
+use crate::formatter::{FmtOptions, Formatter};
 use crate::function::Function;
 use crate::import::Import;
 use crate::item::Item;
 use crate::module::Module;
+use crate::visibility::Visibility;
 
+use crate::r#const::Const;
 use crate::r#enum::Enum;
-use crate::r#impl::Impl;
+use crate::r#impl::{Impl, CONVERSION_TRAITS};
 use crate::r#struct::Struct;
 use crate::r#trait::Trait;
+use crate::r#type::Type;
+use crate::trait_alias::TraitAlias;
 use crate::type_alias::TypeAlias;
+use crate::union::Union;
+
+/// How the items of a scope are ordered when formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemOrdering {
+    /// Items are sorted by name, the default.
+    #[default]
+    Alphabetical,
+    /// Items are emitted in the order they were added.
+    Insertion,
+}
 
 /// Defines a scope.
 ///
 /// A scope contains modules, types, etc...
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Scope {
     /// Scope documentation
     docs: Option<Docs>,
@@ -29,6 +48,33 @@ pub struct Scope {
 
     /// Contents of the documentation,
     items: Vec<Item>,
+
+    /// Whether items are sorted by name or emitted in insertion order
+    ordering: ItemOrdering,
+
+    /// Traits whose impls sort next to the type converted from or to
+    conversion_traits: Vec<String>,
+
+    /// Whether imports are sorted by path or emitted in insertion order
+    sort_imports: bool,
+
+    /// Whether imports sharing a crate are merged into nested groups
+    nest_imports: bool,
+
+    /// Visibility of items without an explicit visibility
+    default_vis: Option<String>,
+
+    /// The `// @generated` marker emitted as the first line, if any
+    generated: Option<String>,
+
+    /// Inner attributes, e.g. `#![no_std]`
+    inner_attrs: Vec<String>,
+
+    /// Whether imports sharing a name are replaced by fully qualified types
+    qualify_collisions: bool,
+
+    /// Options used by `to_string` and the writer methods
+    fmt_options: FmtOptions,
 }
 
 impl Scope {
@@ -38,31 +84,319 @@ impl Scope {
             docs: None,
             imports: IndexMap::new(),
             items: vec![],
+            ordering: ItemOrdering::default(),
+            conversion_traits: CONVERSION_TRAITS.iter().map(ToString::to_string).collect(),
+            sort_imports: false,
+            nest_imports: false,
+            default_vis: None,
+            generated: None,
+            inner_attrs: vec![],
+            qualify_collisions: false,
+            fmt_options: FmtOptions::default(),
+        }
+    }
+
+    /// Set how items are ordered when formatting.
+    ///
+    /// Imports and raw items are always emitted first.
+    pub fn set_ordering(&mut self, ordering: ItemOrdering) -> &mut Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Set the options used by [`Scope::to_string`], [`Scope::fmt_to_writer`]
+    /// and [`Scope::write_to_file`], e.g. the indentation or maximum width.
+    ///
+    /// Formatting with [`Scope::fmt`] uses the options of the given formatter.
+    pub fn set_fmt_options(&mut self, options: FmtOptions) -> &mut Self {
+        self.fmt_options = options;
+        self
+    }
+
+    /// Set the traits whose impls for a generic target are sorted by the
+    /// converted type instead of the trait, e.g. `impl From<Foo> for Vec<u8>`
+    /// sorts next to `Foo`.
+    ///
+    /// Defaults to [`CONVERSION_TRAITS`]. Nested modules keep their own list.
+    pub fn set_conversion_traits<S: ToString>(&mut self, traits: impl IntoIterator<Item = S>) -> &mut Self {
+        self.conversion_traits = traits.into_iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Set whether imports are sorted by path when formatting.
+    ///
+    /// Imports are emitted in the order they were added by default.
+    pub fn sort_imports(&mut self, sort: bool) -> &mut Self {
+        self.sort_imports = sort;
+        self
+    }
+
+    /// Set whether imports sharing a crate are merged into a single `use`
+    /// with nested groups, e.g. `use std::{fmt, io::{self, Write}};`.
+    pub fn nest_imports(&mut self, nest: bool) -> &mut Self {
+        self.nest_imports = nest;
+        self
+    }
+
+    /// Set whether types are written with their full path when their name
+    /// is imported from more than one path.
+    ///
+    /// When enabled, types must be given with their full path, e.g.
+    /// `std::io::Error`. Types matching an import are written by their short
+    /// name, unless another import shares that name, e.g. `std::fmt::Error`.
+    /// In that case the colliding imports are omitted and all of these types
+    /// keep their full path.
+    pub fn qualify_collisions(&mut self, qualify: bool) -> &mut Self {
+        self.qualify_collisions = qualify;
+        self
+    }
+
+    /// Set the visibility used for items without an explicit visibility,
+    /// e.g. `pub(crate)`.
+    ///
+    /// This applies to the items directly contained in this scope, the
+    /// contents of nested modules are not affected.
+    pub fn default_visibility(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.default_vis = vis.into().into_modifier();
+        self
+    }
+
+    /// Emit a `// @generated` marker as the first line of the scope, so
+    /// tooling can recognise the code as generated.
+    ///
+    /// With `Some(tool)`, the marker reads `// @generated by <tool>`.
+    pub fn mark_generated(&mut self, by: Option<&str>) -> &mut Self {
+        self.generated = Some(match by {
+            Some(tool) => format!("// @generated by {}", tool),
+            None => "// @generated".to_string(),
+        });
+        self
+    }
+
+    /// Remove all items and imports, keeping the allocations for reuse.
+    ///
+    /// The documentation is kept if `keep_docs` is set. Formatting options,
+    /// such as sorting, are always kept.
+    pub fn clear(&mut self, keep_docs: bool) -> &mut Self {
+        if !keep_docs {
+            self.docs = None;
+        }
+        self.imports.clear();
+        self.items.clear();
+        self
+    }
+
+    /// Set the scope documentation, emitted as inner doc comments (`//!`) at
+    /// the top of the scope.
+    pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.docs = Some(Docs::new(docs));
+        self
+    }
+
+    /// Add an inner attribute, e.g. `#![deny(missing_docs)]`, at the top of
+    /// the scope.
+    ///
+    /// Adding the same attribute twice has no effect.
+    pub fn inner_attr(&mut self, attribute: impl ToString) -> &mut Self {
+        let attribute = attribute.to_string();
+        if !self.inner_attrs.contains(&attribute) {
+            self.inner_attrs.push(attribute);
         }
+        self
+    }
+
+    /// Add the `#![no_std]` crate attribute.
+    pub fn no_std(&mut self) -> &mut Self {
+        self.inner_attr("no_std")
+    }
+
+    /// Add the `#![no_main]` crate attribute.
+    pub fn no_main(&mut self) -> &mut Self {
+        self.inner_attr("no_main")
+    }
+
+    /// Returns the items of the scope in insertion order.
+    pub fn items(&self) -> &[Item] {
+        &self.items
     }
 
     /// Import a type into the scope.
     ///
     /// This results in a new `use` statement being added to the beginning of
-    /// the scope.
+    /// the scope. The path and type are emitted verbatim, they are never
+    /// parsed as a [`Type`].
     pub fn new_import(&mut self, path: impl ToString, ty: impl ToString, alias: Option<&str>) -> &mut Import {
-        // handle cases where the caller wants to refer to a type namespaced
-        // within the containing namespace, like "a::B".
+        // a type namespaced within the path, like "a::B", is imported by its
+        // full path, so it is deduplicated with an import of "path::a" and "B"
         let ty = ty.to_string();
-        let ty = ty.split("::").next().unwrap_or_else(|| ty.as_str());
+        let (path, ty) = match ty.rsplit_once("::") {
+            Some((prefix, ty)) => (format!("{}::{}", path.to_string(), prefix), ty),
+            None => (path.to_string(), ty.as_str()),
+        };
         self.imports
-            .entry(path.to_string())
-            .or_insert(IndexMap::new())
+            .entry(path.clone())
+            .or_default()
             .entry(ty.to_string())
             .or_insert_with(|| Import::new(path, ty, alias))
     }
 
+    /// Import all public items of `path` with a glob import, e.g.
+    /// `use std::collections::*;`.
+    ///
+    /// Glob imports are emitted on their own line, never grouped.
+    pub fn import_glob(&mut self, path: impl ToString) -> &mut Import {
+        self.new_import(path, "*", None)
+    }
+
     /// Push a new import (`use` statement) ad the beginning of the scope
     pub fn push_import(&mut self, path: impl ToString, ty: impl ToString, alias: Option<&str>) -> &mut Self {
         self.new_import(path, ty, alias);
         self
     }
 
+    /// Move each import into the narrowest modules that use it.
+    ///
+    /// An import that is referenced by the items of a nested module is copied
+    /// into that module, recursively, as a module does not see the imports of
+    /// its parent. It stays in this scope if the other items of the scope use
+    /// it, or if no item uses it at all, and is removed otherwise.
+    ///
+    /// Relative imports (`self::` and `super::` paths) and glob imports are
+    /// never moved, as their meaning depends on the scope. Imports with a
+    /// visibility are re-exports and are never moved either.
+    ///
+    /// References are found by looking for the imported name, or its alias,
+    /// in the generated code of each item.
+    pub fn hoist_imports(&mut self) -> Result<&mut Self, fmt::Error> {
+        let rendered = self
+            .items
+            .iter()
+            .map(render_item)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // (path, name, modules using the import, whether other items use it)
+        let mut moves = vec![];
+
+        for (path, imports) in &self.imports {
+            if path == "self" || path == "super" || path.starts_with("self::") || path.starts_with("super::") {
+                continue;
+            }
+
+            for (ty, import) in imports {
+                if ty == "*" || import.vis.is_some() {
+                    continue;
+                }
+
+                let name = import.alias.as_deref().unwrap_or(ty);
+                let mut modules = vec![];
+                let mut used_here = false;
+
+                for (item, code) in self.items.iter().zip(&rendered) {
+                    if references(code, name) {
+                        match *item {
+                            Item::Module(ref module) => modules.push(module.name.clone()),
+                            _ => used_here = true,
+                        }
+                    }
+                }
+
+                if !modules.is_empty() {
+                    moves.push((path.clone(), ty.clone(), modules, used_here));
+                }
+            }
+        }
+
+        for (path, ty, modules, used_here) in moves {
+            let import = self.imports[&path][&ty].clone();
+
+            if !used_here {
+                let imports = self.imports.get_mut(&path).unwrap();
+                imports.shift_remove(&ty);
+                if imports.is_empty() {
+                    self.imports.shift_remove(&path);
+                }
+            }
+
+            for module in modules {
+                self.get_module_mut(&module)
+                    .unwrap()
+                    .scope()
+                    .imports
+                    .entry(path.clone())
+                    .or_default()
+                    .entry(ty.clone())
+                    .or_insert_with(|| import.clone());
+            }
+        }
+
+        for item in self.items.iter_mut() {
+            if let Item::Module(ref mut module) = *item {
+                module.scope().hoist_imports()?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Convert the scope into a module with the given name.
+    ///
+    /// All items and imports of the scope become the contents of the module.
+    pub fn into_module(self, name: impl ToString) -> Module {
+        let mut module = Module::new(name);
+        *module.scope() = self;
+        module
+    }
+
+    /// Declare file modules and optionally re-export their contents, as in
+    /// an aggregating `mod.rs`.
+    ///
+    /// Each child is given as `(name, visibility, reexport)` and pushes a
+    /// [declaration only](Module::set_declaration_only) module, emitting
+    /// `mod name;`, and a `pub use name::*;` import if `reexport` is set.
+    pub fn aggregate_modules<N, V>(&mut self, children: impl IntoIterator<Item = (N, V, bool)>) -> &mut Self
+    where
+        N: ToString,
+        V: Into<Visibility>,
+    {
+        for (name, vis, reexport) in children {
+            let name = name.to_string();
+            self.new_module(&name).vis(vis).set_declaration_only(true);
+
+            if reexport {
+                self.new_import(name, "*", None).vis("pub");
+            }
+        }
+        self
+    }
+
+    /// Push a module named `name` that is only compiled if `cfg` holds, and
+    /// build its contents with `f`.
+    ///
+    /// This gates a batch of items behind a single `#[cfg(..)]` instead of
+    /// one per item:
+    ///
+    /// ```
+    /// use codegen::{Cfg, Scope};
+    ///
+    /// let mut scope = Scope::new();
+    ///
+    /// // #[cfg(feature = "serde")]
+    /// // mod serde_impls { .. }
+    /// scope.cfg_group("serde_impls", Cfg::feature("serde"), |scope| {
+    ///     scope.new_struct("Foo");
+    ///     scope.new_struct("Bar");
+    /// });
+    /// ```
+    pub fn cfg_group<F>(&mut self, name: impl ToString, cfg: impl ToString, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Scope),
+    {
+        let module = self.new_module(name);
+        module.cfg(cfg);
+        f(module.scope());
+        self
+    }
+
     /// Push a new module definition, returning a mutable reference to it.
     ///
     /// # Panics
@@ -106,7 +440,7 @@ impl Scope {
         self.items
             .iter()
             .filter_map(|item| match item {
-                &Item::Module(ref module) if module.name == *name => Some(module),
+                Item::Module(module) if module.name == *name => Some(module),
                 _ => None,
             })
             .next()
@@ -159,6 +493,22 @@ impl Scope {
         self
     }
 
+    /// Push a new union definition, returning a mutable reference to it.
+    pub fn new_union(&mut self, name: impl ToString) -> &mut Union {
+        self.push_union(Union::new(name));
+
+        match *self.items.last_mut().unwrap() {
+            Item::Union(ref mut v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Push a union definition
+    pub fn push_union(&mut self, item: Union) -> &mut Self {
+        self.items.push(Item::Union(item));
+        self
+    }
+
     /// Push a new function definition, returning a mutable reference to it.
     pub fn new_fn(&mut self, name: impl ToString) -> &mut Function {
         self.push_fn(Function::new(name));
@@ -169,6 +519,14 @@ impl Scope {
         }
     }
 
+    /// Push a new test function, returning a mutable reference to it.
+    ///
+    /// The function is annotated with `#[test]`. Further attributes, such as
+    /// `should_panic`, can be added with [`Function::attr`].
+    pub fn new_test_fn(&mut self, name: impl ToString) -> &mut Function {
+        self.new_fn(name).attr("test")
+    }
+
     /// Push a function definition
     pub fn push_fn(&mut self, item: Function) -> &mut Self {
         self.items.push(Item::Function(item));
@@ -217,12 +575,59 @@ impl Scope {
         }
     }
 
+    /// Push a new `impl Drop` block for `target`, returning a mutable
+    /// reference to its `fn drop(&mut self)` for filling in the body.
+    pub fn new_drop_impl(&mut self, target: impl ToString) -> &mut Function {
+        self.new_impl(target)
+            .impl_trait("Drop")
+            .new_fn("drop")
+            .arg_mut_self()
+    }
+
     /// Push an `impl` block.
     pub fn push_impl(&mut self, item: Impl) -> &mut Self {
         self.items.push(Item::Impl(item));
         self
     }
 
+    /// Push a new `const` item, returning a mutable reference to it.
+    ///
+    /// Use [`Const::set_static`] to turn it into a `static`.
+    pub fn new_const<T>(&mut self, name: impl ToString, ty: T, value: impl ToString) -> &mut Const
+    where
+        T: Into<Type>,
+    {
+        self.push_const(Const::new(name, ty, value));
+
+        match *self.items.last_mut().unwrap() {
+            Item::Const(ref mut v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Push a `const` or `static` item.
+    pub fn push_const(&mut self, item: Const) -> &mut Self {
+        self.items.push(Item::Const(item));
+        self
+    }
+
+    /// Push a new `extern` block with the given ABI, returning a mutable
+    /// reference to it.
+    pub fn new_extern_block(&mut self, abi: impl ToString) -> &mut ExternBlock {
+        self.push_extern_block(ExternBlock::new(abi));
+
+        match *self.items.last_mut().unwrap() {
+            Item::ExternBlock(ref mut v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Push an `extern` block.
+    pub fn push_extern_block(&mut self, item: ExternBlock) -> &mut Self {
+        self.items.push(Item::ExternBlock(item));
+        self
+    }
+
     /// Push a raw string to the scope.
     ///
     /// This string will be included verbatim in the formatted string.
@@ -231,6 +636,22 @@ impl Scope {
         self
     }
 
+    /// Push a new trait alias, returning a mutable reference to it.
+    pub fn new_trait_alias(&mut self, name: impl ToString) -> &mut TraitAlias {
+        self.push_trait_alias(TraitAlias::new(name));
+
+        match *self.items.last_mut().unwrap() {
+            Item::TraitAlias(ref mut v) => v,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Push a trait alias.
+    pub fn push_trait_alias(&mut self, item: TraitAlias) -> &mut Self {
+        self.items.push(Item::TraitAlias(item));
+        self
+    }
+
     /// Push a new `TypeAlias`, returning a mutable reference to it.
     pub fn new_type_alias(&mut self, name: impl ToString, target: impl ToString) -> &mut TypeAlias {
         self.push_type_alias(TypeAlias::new(name, target));
@@ -248,10 +669,11 @@ impl Scope {
     }
 
     /// Return a string representation of the scope.
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         let mut ret = String::new();
 
-        self.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        self.fmt(&mut Formatter::with_options(&mut ret, &self.fmt_options)).unwrap();
 
         // Remove the trailing newline
         if ret.as_bytes().last() == Some(&b'\n') {
@@ -261,8 +683,48 @@ impl Scope {
         ret
     }
 
+    /// Write the scope to `w`, streaming the output instead of building a
+    /// `String` first.
+    ///
+    /// Unlike [`Scope::to_string`], the output keeps its trailing newline.
+    pub fn fmt_to_writer<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut adapter = IoAdapter { inner: w, error: None };
+
+        match self.fmt(&mut Formatter::with_options(&mut adapter, &self.fmt_options)) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+
+    /// Write the scope to the file at `path`, creating or truncating it.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.fmt_to_writer(&mut file)?;
+        io::Write::flush(&mut file)
+    }
+
     /// Formats the scope using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(ref generated) = self.generated {
+            writeln!(fmt, "{}", generated)?;
+        }
+
+        if let Some(ref docs) = self.docs {
+            docs.fmt_inner(fmt)?;
+        }
+
+        for attr in &self.inner_attrs {
+            writeln!(fmt, "#![{}]", attr)?;
+        }
+
+        if (self.docs.is_some() || !self.inner_attrs.is_empty())
+            && (!self.imports.is_empty() || !self.items.is_empty())
+        {
+            writeln!(fmt)?;
+        }
+
         {
             let mut has_raw = false;
             for item in self.items.iter() {
@@ -276,54 +738,60 @@ impl Scope {
             }
         }
 
+        fmt.with_type_names(self.type_names(), |fmt| self.fmt_body(fmt))
+    }
+
+    fn fmt_body(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         {
             self.fmt_imports(fmt)?;
-            if !self.imports.is_empty() {
+            let colliding = self.colliding_names();
+            let has_import = self
+                .imports
+                .values()
+                .flatten()
+                .any(|(ty, import)| import.alias.is_some() || !colliding.contains(&ty.as_str()));
+            let has_item = self.items.iter().any(|item| !matches!(*item, Item::Raw(_)));
+            if has_import && has_item {
                 writeln!(fmt)?;
             }
         }
 
-        let mut sorted_items = BTreeMap::<String, Vec<&Item>>::new();
-        for item in self.items.iter() {
-            match *item {
-                Item::Module(ref v) => sorted_items.entry(format!("{}-module", v.name)).or_default().push(item),
-                // note: purposely use `astruct` instead of `struct` to make sure the struct always comes first in alphabetical order
-                Item::Struct(ref v) => sorted_items.entry(format!("{}-astruct", v.ty().key_for_sorting())).or_default().push(item),
-                Item::Function(ref v) => sorted_items.entry(format!("{}-function", v.name())).or_default().push(item),
-                Item::Trait(ref v) => sorted_items.entry(format!("{}-trait", v.ty().key_for_sorting())).or_default().push(item),
-                Item::Enum(ref v) => sorted_items.entry(format!("{}-enum", v.ty().key_for_sorting())).or_default().push(item),
-                Item::Impl(ref v) => sorted_items.entry(format!("{}-impl", v.key_for_sorting().key_for_sorting())).or_default().push(item),
-                Item::TypeAlias(ref v) => sorted_items.entry(format!("{}-alias", v.type_def().key_for_sorting())).or_default().push(item),
-                _ => {},
+        let items = if self.ordering == ItemOrdering::Alphabetical {
+            let mut sorted_items = BTreeMap::<_, Vec<&Item>>::new();
+            for item in self.items.iter() {
+                if let Some(key) = item.sort_key(&self.conversion_traits) {
+                    sorted_items.entry(key).or_default().push(item);
+                }
             }
-        }
+            sorted_items.into_values().flatten().collect::<Vec<_>>()
+        } else {
+            self.items.iter().collect::<Vec<_>>()
+        };
 
         {
             let mut has_item = false;
-            for key_vals in sorted_items.iter() {
-                for item in key_vals.1.iter() {
-                    match *item {
-                        Item::Raw(_) => {}
-                        _ => {
-                            if has_item {
-                                writeln!(fmt)?;
-                            } else {
-                                has_item = true;
-                            }
-                        },
-                    }
+            for item in items {
+                match *item {
+                    Item::Raw(_) => {}
+                    _ => {
+                        if has_item {
+                            writeln!(fmt)?;
+                        } else {
+                            has_item = true;
+                        }
+                    },
+                }
 
-                    match *item {
-                        Item::Module(ref v) => v.fmt(fmt)?,
-                        Item::Struct(ref v) => v.fmt(fmt)?,
-                        Item::Function(ref v) => v.fmt(false, fmt)?,
-                        Item::Trait(ref v) => v.fmt(fmt)?,
-                        Item::Enum(ref v) => v.fmt(fmt)?,
-                        Item::Impl(ref v) => v.fmt(fmt)?,
-                        Item::TypeAlias(ref v) => v.fmt(fmt)?,
-                        _ => {}, // already printed earlier
+                match (item, self.default_vis.as_deref()) {
+                    (Item::Raw(_), _) => {}, // already printed earlier
+                    // modules are not cloned, as they contain whole scopes
+                    (Item::Module(module), default_vis) => module.fmt_with_default_vis(default_vis, fmt)?,
+                    (item, Some(vis)) if item.needs_default_vis() => {
+                        let mut item = item.clone();
+                        item.default_vis(vis);
+                        item.fmt(fmt)?;
                     }
-                    
+                    (item, _) => item.fmt(fmt)?,
                 }
             }
         }
@@ -331,53 +799,130 @@ impl Scope {
         Ok(())
     }
 
+    /// Returns the names of imports which are imported from more than one
+    /// path, if collisions are qualified.
+    fn colliding_names(&self) -> Vec<&str> {
+        let mut seen = vec![];
+        let mut colliding = vec![];
+
+        if self.qualify_collisions {
+            for (ty, import) in self.imports.values().flatten() {
+                if import.alias.is_some() || ty == "*" || ty == "self" {
+                    continue;
+                }
+                if seen.contains(&ty.as_str()) {
+                    if !colliding.contains(&ty.as_str()) {
+                        colliding.push(ty.as_str());
+                    }
+                } else {
+                    seen.push(ty.as_str());
+                }
+            }
+        }
+
+        colliding
+    }
+
+    /// Returns the names types are rendered as, keyed by their full path.
+    fn type_names(&self) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+
+        if self.qualify_collisions {
+            let colliding = self.colliding_names();
+
+            for (path, imports) in &self.imports {
+                for (ty, import) in imports {
+                    if import.alias.is_none() && ty != "*" && ty != "self" && !colliding.contains(&ty.as_str()) {
+                        names.insert(format!("{}::{}", path, ty), ty.clone());
+                    }
+                }
+            }
+        }
+
+        names
+    }
+
     fn fmt_imports(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
-        // First, collect all visibilities
-        let mut visibilities = vec![];
+        let colliding = self.colliding_names();
+
+        // First, collect all visibilities and `cfg` attributes, imports are
+        // only grouped with those sharing both
+        let mut groups = vec![];
 
         for (_, imports) in &self.imports {
             for (_, import) in imports {
-                if !visibilities.contains(&import.vis) {
-                    visibilities.push(import.vis.clone());
+                let group = (&import.vis, &import.cfg);
+                if !groups.contains(&group) {
+                    groups.push(group);
                 }
             }
         }
 
+        let mut paths = self.imports.iter().collect::<Vec<_>>();
+        if self.sort_imports {
+            paths.sort_by(|a, b| a.0.cmp(b.0));
+        }
+
         let mut alias_tys = vec![];
         let mut simple_tys = vec![];
 
-        // Loop over all visibilities and format the associated imports
-        for vis in &visibilities {
-            for (path, imports) in &self.imports {
+        // Loop over all groups and format the associated imports
+        for &(vis, cfgs) in &groups {
+            let mut tree = ImportTree::default();
+
+            for &(path, imports) in &paths {
                 alias_tys.clear();
                 simple_tys.clear();
 
                 for (ty, import) in imports {
-                    if *vis == import.vis {
+                    if *vis == import.vis
+                        && *cfgs == import.cfg
+                        && (import.alias.is_some() || !colliding.contains(&ty.as_str()))
+                    {
                         match import.alias.as_ref() {
-                            None => { simple_tys.push(ty); }
+                            // glob imports are never grouped
+                            None if ty == "*" => { alias_tys.push(ty.to_string()); }
+                            None => { simple_tys.push(ty.to_string()); }
+                            // re-exports group renamed items with the others
+                            Some(alias) if vis.is_some() => { simple_tys.push(format!("{} as {}", ty, alias)); }
                             Some(alias) => { alias_tys.push(format!("{} as {}", ty, alias)); }
                         }
                     }
                 }
 
+                if self.sort_imports {
+                    alias_tys.sort();
+                    // `self` always comes first in a group
+                    simple_tys.sort_by(|a, b| (a != "self", a).cmp(&(b != "self", b)));
+                }
+
+                if self.nest_imports {
+                    for ty in simple_tys.iter().chain(&alias_tys) {
+                        tree.insert(path.split("::").chain(ty.split("::")));
+                    }
+                    continue;
+                }
+
                 for ty in alias_tys.iter() {
+                    for cfg in cfgs {
+                        writeln!(fmt, "#[cfg({})]", cfg)?;
+                    }
                     if let Some(ref vis) = *vis {
                         write!(fmt, "{} ", vis)?;
                     }
 
-                    write!(fmt, "use {}::{};\n", path, ty)?;
+                    writeln!(fmt, "use {};", join_import(path, ty))?;
                 }
                 if !simple_tys.is_empty() {
+                    for cfg in cfgs {
+                        writeln!(fmt, "#[cfg({})]", cfg)?;
+                    }
                     if let Some(ref vis) = *vis {
                         write!(fmt, "{} ", vis)?;
                     }
 
-                    write!(fmt, "use {}::", path)?;
-
                     if simple_tys.len() > 1 {
-                        write!(fmt, "{{")?;
-
+                        write!(fmt, "use {}::{{", path)?;
                         for (i, ty) in simple_tys.iter().enumerate() {
                             if i != 0 {
                                 write!(fmt, ", ")?;
@@ -385,34 +930,165 @@ impl Scope {
                             write!(fmt, "{}", ty)?;
                         }
 
-                        write!(fmt, "}};\n")?;
-                    } else if simple_tys.len() == 1 {
-                        write!(fmt, "{};\n", simple_tys[0])?;
+                        writeln!(fmt, "}};")?;
+                    } else {
+                        writeln!(fmt, "use {};", join_import(path, &simple_tys[0]))?;
                     }
                 }
             }
+
+            if self.sort_imports {
+                tree.sort();
+            }
+
+            for (root, node) in &tree.children {
+                for cfg in cfgs {
+                    writeln!(fmt, "#[cfg({})]", cfg)?;
+                }
+                if let Some(ref vis) = *vis {
+                    write!(fmt, "{} ", vis)?;
+                }
+
+                writeln!(fmt, "use {};", node.render(root))?;
+            }
         }
 
         Ok(())
     }
 
     /// Merge two scopes together
+    ///
+    /// The docs, imports and items of `other` are appended to this scope.
+    /// Formatting options, such as sorting, are kept from this scope.
+    ///
+    /// Imports already present in this scope are skipped, as are items equal
+    /// to an item already present, e.g. a struct defined identically by both
+    /// scopes. Raw items are always appended.
     pub fn append(&mut self, other: &Self) -> &Self {
         self.docs = match (self.docs.as_ref(), other.docs.as_ref()) {
-            (Some(doc_a), Some(doc_b)) => Some(Docs::new("").append(doc_a.to_str()).append(doc_b.to_str()).clone()),
+            (Some(doc_a), Some(doc_b)) => Some(doc_a.clone().append(doc_b.to_str()).clone()),
             (Some(doc_a), None) => Some(doc_a.clone()),
             (None, Some(doc_b)) => Some(doc_b.clone()),
             (None, None) => None,
         };
         for (key, value) in other.imports.iter() {
-            self.imports
+            let imports = self
+                .imports
                 .entry(key.to_string())
-                .or_insert(IndexMap::new())
-                .extend(value.iter().map(|(a,b)| (a.clone(), b.clone())));
+                .or_default();
+            for (ty, import) in value {
+                imports.entry(ty.clone()).or_insert_with(|| import.clone());
+            }
         }
 
-        self.items.extend(other.items.iter().cloned());
+        for item in &other.items {
+            if item.sort_key(&[]).is_none() || !self.items.contains(item) {
+                self.items.push(item.clone());
+            }
+        }
         self
     }
 }
 
+impl Default for Scope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the code generated for `item`.
+fn render_item(item: &Item) -> Result<String, fmt::Error> {
+    let mut code = String::new();
+    item.fmt(&mut Formatter::new(&mut code))?;
+    Ok(code)
+}
+
+/// Joins an imported item to its path outside of a group, where `self` is
+/// invalid, e.g. `use a::b::self as c;` is written `use a::b as c;`.
+fn join_import(path: &str, ty: &str) -> String {
+    match ty.strip_prefix("self") {
+        Some(rest) if rest.is_empty() || rest.starts_with(" as ") => format!("{}{}", path, rest),
+        _ => format!("{}::{}", path, ty),
+    }
+}
+
+/// Imports merged by their path segments, e.g. `std::{fmt, io::{self, Write}}`.
+#[derive(Default)]
+struct ImportTree {
+    /// Whether the path itself is imported, i.e. as `self`
+    terminal: bool,
+    children: IndexMap<String, ImportTree>,
+}
+
+impl ImportTree {
+    fn insert<'a>(&mut self, segments: impl IntoIterator<Item = &'a str>) {
+        let mut node = self;
+        for segment in segments {
+            if segment == "self" {
+                node.terminal = true;
+                return;
+            }
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        node.terminal = true;
+    }
+
+    /// Sorts the segments by name at every level.
+    fn sort(&mut self) {
+        self.children.sort_keys();
+        for child in self.children.values_mut() {
+            child.sort();
+        }
+    }
+
+    /// Returns the tree below `name` as written in a `use`.
+    fn render(&self, name: &str) -> String {
+        let mut parts = self.terminal.then(|| "self".to_string()).into_iter().collect::<Vec<_>>();
+        parts.extend(self.children.iter().map(|(segment, child)| child.render(segment)));
+
+        match parts.as_slice() {
+            [] => name.to_string(),
+            [only] => join_import(name, only),
+            _ => format!("{}::{{{}}}", name, parts.join(", ")),
+        }
+    }
+}
+
+/// Forwards formatted output to an `io::Write`, keeping the first error.
+struct IoAdapter<'w, W> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<'w, W: io::Write> fmt::Write for IoAdapter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Returns whether `name` appears as a whole identifier in `code`.
+fn references(code: &str, name: &str) -> bool {
+    code.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|ident| ident == name)
+}
+
+#[test]
+fn append_docs() {
+    let mut a = Scope::new();
+    a.docs = Some(Docs::new("Docs of A\nsecond line"));
+    let mut b = Scope::new();
+    b.docs = Some(Docs::new("Docs of B"));
+
+    a.append(&b);
+    assert_eq!(a.docs.unwrap().to_str(), "Docs of A\nsecond line\nDocs of B");
+}
+
+#[test]
+fn references_whole_identifiers() {
+    assert!(references("let map: HashMap<u8, u8>;", "HashMap"));
+    assert!(!references("let map: MyHashMap<u8, u8>;", "HashMap"));
+    assert!(!references("let map = hash_map::new();", "hash"));
+}
@@ -1,14 +1,17 @@
 use std::fmt::{self, Write};
 
-use crate::field::Field;
+use crate::block::Block;
+use crate::field::{Field, TupleField};
 use crate::fields::Fields;
 use crate::formatter::Formatter;
+use crate::visibility::Visibility;
+use crate::r#impl::Impl;
 use crate::type_def::TypeDef;
 
 use crate::r#type::Type;
 
 /// Defines a struct.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Struct {
     type_def: TypeDef,
 
@@ -30,19 +33,47 @@ impl Struct {
         &self.type_def.ty
     }
 
+    /// Returns a reference to the struct's fields.
+    pub fn fields(&self) -> &Fields {
+        &self.fields
+    }
+
     /// Set the structure visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.type_def.vis(vis);
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
         self
     }
 
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
     /// Add a generic to the struct.
+    ///
+    /// Generics that were already added are ignored.
     pub fn generic(&mut self, name: impl ToString) -> &mut Self {
-        self.type_def.ty.generic(name);
+        self.type_def.generic(name);
+        self
+    }
+
+    /// Add a lifetime to the struct, e.g. `'a`.
+    ///
+    /// Lifetimes are emitted before the other generics.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.lifetime(name);
         self
     }
 
     /// Add a `where` bound to the struct.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
@@ -51,24 +82,64 @@ impl Struct {
         self
     }
 
+    /// Sort the `where` clause predicates by name when formatting the struct.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_bounds(sort);
+        self
+    }
+
     /// Set the structure documentation.
     pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
         self.type_def.doc(docs);
         self
     }
 
+    /// Add a `#[doc = <expr>]` attribute whose value is an expression rather
+    /// than a string literal, e.g. `concat!("Since ", env!("CARGO_PKG_VERSION"))`.
+    pub fn doc_expr(&mut self, expr: impl ToString) -> &mut Self {
+        self.type_def.doc_expr(expr);
+        self
+    }
+
     /// Add a new type that the struct should derive.
+    ///
+    /// Derives that were already added are ignored.
     pub fn derive(&mut self, name: impl ToString) -> &mut Self {
         self.type_def.derive(name);
         self
     }
 
+    /// Sort the derives alphabetically when formatting the struct.
+    pub fn sort_derives(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_derives(sort);
+        self
+    }
+
+    /// Emit each derive of the struct as its own `#[derive(..)]` attribute.
+    pub fn split_derives(&mut self, split: bool) -> &mut Self {
+        self.type_def.split_derives(split);
+        self
+    }
+
     /// Add a new attribute to the struct
     pub fn attr(&mut self, attr: impl ToString) -> &mut Self {
         self.type_def.attr(attr);
         self
     }
 
+    /// Add a `#[serde(rename_all = "...")]` attribute, e.g. with `camelCase`.
+    pub fn serde_rename_all(&mut self, case: impl ToString) -> &mut Self {
+        self.type_def.attr(format!("serde(rename_all = {:?})", case.to_string()));
+        self
+    }
+
+    /// Emit `#[rustfmt::skip]` above the struct, e.g. to keep a hand-aligned
+    /// layout.
+    pub fn skip_rustfmt(&mut self) -> &mut Self {
+        self.type_def.skip_rustfmt();
+        self
+    }
+
     /// Specify lint attribute to supress a warning or error.
     pub fn allow(&mut self, allow: impl ToString) -> &mut Self {
         self.type_def.allow(allow);
@@ -87,12 +158,25 @@ impl Struct {
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the struct.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
     /// Add a cfg_attr to include attributes based on a configuration predicate
     pub fn cfg_attr(&mut self, cfg_attr: impl ToString) -> &mut Self {
         self.type_def.cfg_attr(cfg_attr);
         self
     }
 
+    /// Add a cfg_attr from a configuration predicate and the attribute to
+    /// include when it holds, e.g. `#[cfg_attr(test, derive(PartialEq))]`.
+    pub fn cfg_attr_with(&mut self, cond: impl ToString, content: impl ToString) -> &mut Self {
+        self.type_def.cfg_attr_with(cond, content);
+        self
+    }
+
     /// Push a named field to the struct.
     ///
     /// A struct can either set named fields with this function or tuple fields
@@ -125,6 +209,15 @@ impl Struct {
         self.fields.new_named(name, ty)
     }
 
+    /// Push a tuple field to the struct.
+    ///
+    /// A struct can either set tuple fields with this function or named fields
+    /// with `push_field`, but not both.
+    pub fn push_tuple_field(&mut self, field: TupleField) -> &mut Self {
+        self.fields.push_tuple(field);
+        self
+    }
+
     /// Add a tuple field to the struct.
     ///
     /// A struct can either set tuple fields with this function or named fields
@@ -137,19 +230,191 @@ impl Struct {
         self
     }
 
+    /// Add a tuple field with the given visibility to the struct, e.g.
+    /// `pub` for `struct Meters(pub f64);`.
+    ///
+    /// A struct can either set tuple fields with this function or named fields
+    /// with `field`, but not both.
+    pub fn tuple_field_vis<T>(&mut self, vis: impl Into<Visibility>, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.fields.tuple(vis.into().into_modifier(), ty);
+        self
+    }
+
+    /// Create a tuple field for the struct, returning a mutable reference to
+    /// it, e.g. to add annotations.
+    ///
+    /// A struct can either set tuple fields with this function or named fields
+    /// with `new_field`, but not both.
+    pub fn new_tuple_field<T>(&mut self, ty: T) -> &mut TupleField
+    where
+        T: Into<Type>,
+    {
+        self.fields.new_tuple(ty)
+    }
+
+    /// Generate an `impl Default` block for the struct.
+    ///
+    /// Named fields use the expression set with [`Field::default_expr`] and
+    /// fall back to `Default::default()` otherwise. Each type parameter of
+    /// the struct is bounded by `Default` and the `where` bounds of the
+    /// struct are kept.
+    pub fn generate_default(&self) -> Impl {
+        let mut imp = self.type_def.impl_trait_for("Default");
+
+        let func = imp.new_fn("default").ret("Self");
+
+        match self.fields {
+            Fields::Empty => {
+                func.line("Self");
+            }
+            Fields::Tuple(ref tys) => {
+                let values = vec!["Default::default()"; tys.len()];
+                func.line(format!("Self({})", values.join(", ")));
+            }
+            Fields::Named(ref fields) => {
+                let mut block = Block::new("Self");
+                for field in fields {
+                    let value = field.default_expr.as_deref().unwrap_or("Default::default()");
+                    block.line(format!("{}: {},", field.name, value));
+                }
+                func.push_block(block);
+            }
+        }
+
+        imp
+    }
+
+    /// Generate `impl PartialEq` and `impl Eq` blocks for the struct,
+    /// comparing only the fields named in `only`, e.g. an identifier.
+    ///
+    /// Tuple fields are named by their index, e.g. `"0"`. If the struct also
+    /// implements `Hash`, it must only hash fields listed in `only`, so that
+    /// equal values have equal hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a name in `only` is not a field of the struct.
+    pub fn generate_eq(&self, only: &[&str]) -> Vec<Impl> {
+        let names = match self.fields {
+            Fields::Empty => Vec::new(),
+            Fields::Tuple(ref tys) => (0..tys.len()).map(|i| i.to_string()).collect(),
+            Fields::Named(ref fields) => fields.iter().map(|f| f.name.clone()).collect(),
+        };
+
+        for name in only {
+            assert!(
+                names.iter().any(|n| n == name),
+                "struct `{}` has no field `{}`",
+                self.ty().name(),
+                name
+            );
+        }
+
+        let comparisons = only
+            .iter()
+            .map(|name| format!("self.{name} == other.{name}", name = name))
+            .collect::<Vec<_>>();
+
+        let mut partial_eq = self.type_def.impl_trait_for("PartialEq");
+        partial_eq
+            .new_fn("eq")
+            .arg_ref_self()
+            .arg("other", "&Self")
+            .ret("bool")
+            .line(if comparisons.is_empty() {
+                "true".to_string()
+            } else {
+                comparisons.join(" && ")
+            });
+
+        vec![partial_eq, self.type_def.impl_trait_for("Eq")]
+    }
+
+    /// Returns a struct literal expression with the given field values,
+    /// e.g. `Foo { x: 1, y: 2 }`.
+    pub fn to_literal<N, V>(&self, values: impl IntoIterator<Item = (N, V)>) -> String
+    where
+        N: ToString,
+        V: ToString,
+    {
+        self.literal(values, None)
+    }
+
+    /// Returns a struct literal expression with the given field values and
+    /// the remaining fields taken from `rest`, e.g. `Foo { x: 1, ..base }`.
+    pub fn to_literal_with_rest<N, V>(
+        &self,
+        values: impl IntoIterator<Item = (N, V)>,
+        rest: impl ToString,
+    ) -> String
+    where
+        N: ToString,
+        V: ToString,
+    {
+        self.literal(values, Some(rest.to_string()))
+    }
+
+    fn literal<N, V>(&self, values: impl IntoIterator<Item = (N, V)>, rest: Option<String>) -> String
+    where
+        N: ToString,
+        V: ToString,
+    {
+        let mut parts = values
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}", name.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = rest {
+            parts.push(format!("..{}", rest));
+        }
+
+        if parts.is_empty() {
+            format!("{} {{}}", self.ty().name())
+        } else {
+            format!("{} {{ {} }}", self.ty().name(), parts.join(", "))
+        }
+    }
+
     /// Formats the struct using the given formatter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the struct is `#[repr(transparent)]` but has more than one
+    /// field besides `PhantomData` markers.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
-        self.type_def.fmt_head("struct", &[], fmt)?;
-        self.fields.fmt(fmt)?;
+        if self.type_def.has_repr("transparent") {
+            let non_marker_fields = self
+                .fields
+                .iter_types()
+                .filter(|ty| ty.key_for_sorting() != "PhantomData")
+                .count();
+
+            assert!(
+                non_marker_fields <= 1,
+                "`#[repr(transparent)]` struct `{}` must have at most one non-`PhantomData` field",
+                self.ty().name()
+            );
+        }
 
         match self.fields {
-            Fields::Empty => {
-                write!(fmt, ";\n")?;
+            Fields::Named(..) => {
+                self.type_def.fmt_head("struct", &[], fmt)?;
+                self.fields.fmt(fmt)?;
             }
-            Fields::Tuple(..) => {
-                write!(fmt, ";\n")?;
+            Fields::Empty | Fields::Tuple(..) => {
+                // the `where` clause of unit and tuple structs follows the fields
+                self.type_def.fmt_head_without_bounds("struct", &[], fmt)?;
+                self.fields.fmt(fmt)?;
+
+                if self.type_def.has_bounds() {
+                    self.type_def.fmt_bounds_ending(";", fmt)?;
+                } else {
+                    writeln!(fmt, ";")?;
+                }
             }
-            _ => {}
         }
 
         Ok(())
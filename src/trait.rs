@@ -6,11 +6,12 @@ use crate::bound::Bound;
 use crate::formatter::{fmt_bound_rhs, Formatter};
 use crate::function::Function;
 use crate::type_def::TypeDef;
+use crate::visibility::Visibility;
 
 use crate::r#type::Type;
 
 /// Define a trait.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Trait {
     type_def: TypeDef,
     parents: Vec<Type>,
@@ -18,7 +19,6 @@ pub struct Trait {
     attributes: Vec<String>,
     associated_tys: Vec<AssociatedType>,
     fns: Vec<Function>,
-    macros: Vec<String>,
 }
 
 impl Trait {
@@ -31,7 +31,6 @@ impl Trait {
             attributes: Vec::new(),
             associated_tys: Vec::new(),
             fns: Vec::new(),
-            macros: Vec::new(),
         }
     }
 
@@ -41,11 +40,22 @@ impl Trait {
     }
 
     /// Set the trait visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.type_def.vis(vis);
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
         self
     }
 
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
     /// Attr
     pub fn attr(&mut self, attr: impl ToString) -> &mut Self {
         self.attributes.push(attr.to_string());
@@ -53,12 +63,16 @@ impl Trait {
     }
 
     /// Add a generic to the trait
+    ///
+    /// Generics that were already added are ignored.
     pub fn generic(&mut self, name: impl ToString) -> &mut Self {
-        self.type_def.ty.generic(name);
+        self.type_def.generic(name);
         self
     }
 
     /// Add a `where` bound to the trait.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
@@ -67,18 +81,37 @@ impl Trait {
         self
     }
 
+    /// Sort the `where` clause predicates by name when formatting the trait.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_bounds(sort);
+        self
+    }
+
     /// Add a macro to the trait def (e.g. `"#[async_trait]"`)
     pub fn r#macro(&mut self, r#macro: impl ToString) -> &mut Self {
         self.type_def.r#macro(r#macro);
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the trait.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
     /// Add a cfg_attr to include attributes based on a configuration predicate
     pub fn cfg_attr(&mut self, cfg_attr: impl ToString) -> &mut Self {
         self.type_def.cfg_attr(cfg_attr);
         self
     }
 
+    /// Add a cfg_attr from a configuration predicate and the attribute to
+    /// include when it holds, e.g. `#[cfg_attr(test, derive(PartialEq))]`.
+    pub fn cfg_attr_with(&mut self, cond: impl ToString, content: impl ToString) -> &mut Self {
+        self.type_def.cfg_attr_with(cond, content);
+        self
+    }
+
     /// Add a parent trait.
     pub fn parent<T>(&mut self, ty: T) -> &mut Self
     where
@@ -94,6 +127,13 @@ impl Trait {
         self
     }
 
+    /// Add a `#[doc = <expr>]` attribute whose value is an expression rather
+    /// than a string literal, e.g. `concat!("Since ", env!("CARGO_PKG_VERSION"))`.
+    pub fn doc_expr(&mut self, expr: impl ToString) -> &mut Self {
+        self.type_def.doc_expr(expr);
+        self
+    }
+
     /// Add an associated const. Returns a mutable reference to the new
     /// associated const for futher configuration.
     pub fn associated_const<T>(&mut self, name: impl ToString, ty: T) -> &mut AssociatedConst
@@ -103,26 +143,28 @@ impl Trait {
         self.associated_consts.push(AssociatedConst(Bound {
             name: name.to_string(),
             bound: vec![ty.into()],
+            lhs: None,
         }));
 
         self.associated_consts.last_mut().unwrap()
     }
 
     /// Add an associated type. Returns a mutable reference to the new
-    /// associated type for futher configuration.
+    /// associated type for futher configuration, e.g. to add bounds or
+    /// generics.
     pub fn associated_type(&mut self, name: impl ToString) -> &mut AssociatedType {
-        self.associated_tys.push(AssociatedType(Bound {
-            name: name.to_string(),
-            bound: vec![],
-        }));
+        self.associated_tys.push(AssociatedType::new(name));
 
         self.associated_tys.last_mut().unwrap()
     }
 
     /// Push a new function definition, returning a mutable reference to it.
+    ///
+    /// The function is a required method until a line or block is pushed,
+    /// which makes it a provided method with a default body.
     pub fn new_fn(&mut self, name: impl ToString) -> &mut Function {
         let mut func = Function::new(name);
-        func.body = None;
+        func.remove_body();
 
         self.push_fn(func);
         self.fns.last_mut().unwrap()
@@ -158,29 +200,20 @@ impl Trait {
                         fmt_bound_rhs(&cst.bound, fmt)?;
                     }
 
-                    write!(fmt, ";\n")?;
+                    writeln!(fmt, ";")?;
                 }
             }
 
             // format associated types
             if !assoc_tys.is_empty() {
                 for ty in assoc_tys {
-                    let ty = &ty.0;
-
-                    write!(fmt, "type {}", ty.name)?;
-
-                    if !ty.bound.is_empty() {
-                        write!(fmt, ": ")?;
-                        fmt_bound_rhs(&ty.bound, fmt)?;
-                    }
-
-                    write!(fmt, ";\n")?;
+                    ty.fmt(fmt)?;
                 }
             }
 
             for (i, func) in self.fns.iter().enumerate() {
                 if i != 0 || !assoc_tys.is_empty() || !assoc_csts.is_empty() {
-                    write!(fmt, "\n")?;
+                    writeln!(fmt)?;
                 }
 
                 func.fmt(true, fmt)?;
@@ -0,0 +1,112 @@
+use std::fmt::{self, Write};
+
+use crate::formatter::{fmt_bound_rhs, Formatter};
+use crate::type_def::TypeDef;
+use crate::visibility::Visibility;
+
+use crate::r#type::Type;
+
+/// Defines a trait alias, e.g. `trait Service = Send + Sync + 'static;`.
+///
+/// Trait aliases are unstable and require `#![feature(trait_alias)]` on a
+/// nightly compiler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitAlias {
+    type_def: TypeDef,
+    bounds: Vec<Type>,
+}
+
+impl TraitAlias {
+    /// Return a trait alias definition with the provided name
+    pub fn new(name: impl ToString) -> Self {
+        TraitAlias {
+            type_def: TypeDef::new(name),
+            bounds: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the type
+    pub fn ty(&self) -> &Type {
+        &self.type_def.ty
+    }
+
+    /// Set the trait alias visibility.
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
+        self
+    }
+
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
+    /// Add a generic to the trait alias.
+    ///
+    /// Generics that were already added are ignored.
+    pub fn generic(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.generic(name);
+        self
+    }
+
+    /// Add a trait or lifetime the alias stands for, e.g. `Send`.
+    pub fn bound<T>(&mut self, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.bounds.push(ty.into());
+        self
+    }
+
+    /// Add a `where` bound to the trait alias.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
+    pub fn where_bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.type_def.bound(name, ty);
+        self
+    }
+
+    /// Set the trait alias documentation.
+    pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.type_def.doc(docs);
+        self
+    }
+
+    /// Add a `cfg` attribute to conditionally compile the trait alias.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
+    /// Formats the trait alias using the given formatter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trait alias has no bounds.
+    pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        assert!(
+            !self.bounds.is_empty(),
+            "trait alias `{}` must have at least one bound",
+            self.ty().name()
+        );
+
+        self.type_def.fmt_head_without_bounds("trait", &[], fmt)?;
+        write!(fmt, " = ")?;
+        fmt_bound_rhs(&self.bounds, fmt)?;
+
+        if self.type_def.has_bounds() {
+            self.type_def.fmt_bounds_ending(";", fmt)
+        } else {
+            writeln!(fmt, ";")
+        }
+    }
+}
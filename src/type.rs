@@ -1,75 +1,401 @@
-use std::fmt::{self, Write};
+use std::error::Error;
+use std::fmt::{self, Display, Write};
 
 use crate::formatter::Formatter;
 
 /// Defines a type.
-#[derive(Debug, Clone)]
+///
+/// Types compare and hash structurally, including the order of their
+/// generics, e.g. `Vec<u8>` differs from `Vec<u16>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Type {
     name: String,
     generics: Vec<Type>,
+    kind: Kind,
+    empty_generics: bool,
+}
+
+/// The error returned by [`Type::new_checked`] for a malformed type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParseError {
+    input: String,
+    reason: String,
+}
+
+impl TypeParseError {
+    fn new(input: &str, reason: impl ToString) -> Self {
+        TypeParseError {
+            input: input.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Returns the string that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns why the string failed to parse.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for TypeParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "malformed type `{}`: {}", self.input, self.reason)
+    }
+}
+
+impl Error for TypeParseError {}
+
+/// The shape of a type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Kind {
+    /// A named type such as `Vec<u8>`, `generics` holds its arguments.
+    Path,
+    /// A tuple type such as `(u8, String)`, `generics` holds its elements.
+    Tuple,
+    /// An `impl Trait` type such as `impl Into<String>`, `generics` holds
+    /// its bounds.
+    ImplTrait,
+    /// A trait object type such as `dyn Error + Send`, `generics` holds its
+    /// bounds.
+    DynTrait,
+    /// A reference type such as `&'a mut T`, `generics` holds the
+    /// referenced type.
+    Reference {
+        lifetime: Option<String>,
+        mutable: bool,
+    },
+    /// An array type such as `[u8; 4]`, `generics` holds the element type.
+    Array {
+        len: String,
+    },
+    /// A slice type such as `[u8]`, `generics` holds the element type.
+    Slice,
+}
+
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+
+/// Returns `true` if the name refers to `Self` or a primitive type, which
+/// must never be rewritten.
+fn is_self_or_primitive(name: &str) -> bool {
+    name == "Self" || name.starts_with("Self::") || PRIMITIVES.contains(&name)
+}
+
+/// Returns `name` as a lifetime, adding the leading `'` if it is missing.
+pub(crate) fn lifetime_name(name: impl ToString) -> String {
+    let name = name.to_string();
+    if name.starts_with('\'') {
+        name
+    } else {
+        format!("'{}", name)
+    }
 }
 
 fn split_name_and_generic(ast: &syn::Type) -> Type {
-    match ast {
-        syn::Type::Path(syn::TypePath { path, .. }) => {
-            let segments = &path.segments;
-            let base_type = segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<String>>().join("::");
-            let mut new_type = Type::new(&base_type);
-
-            if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }) = &segments.last().unwrap().arguments {
-                for arg in args.iter() {
-                    if let syn::GenericArgument::Type(t) = arg {
-                        let generic_type = split_name_and_generic(t);
-                        new_type.generic(generic_type);
-                    } else {
-                        // this isn't correct, but properly parsing the full AST is too tedious and abandoning early here is good enough
-                        return Type {
-                            name: quote::quote! { #ast }.to_string(),
-                            generics: vec![]
-                        }
+    let parsed = match ast {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => split_path(path),
+        syn::Type::ImplTrait(syn::TypeImplTrait { bounds, .. }) => split_bounds(bounds).map(Type::impl_trait),
+        syn::Type::TraitObject(syn::TypeTraitObject { dyn_token: Some(_), bounds }) => {
+            split_bounds(bounds).map(Type::dyn_trait)
+        }
+        syn::Type::Paren(syn::TypeParen { elem, .. }) => Some(split_name_and_generic(elem)),
+        syn::Type::Reference(syn::TypeReference { lifetime, mutability, elem, .. }) => Some(Type {
+            name: String::new(),
+            generics: vec![split_name_and_generic(elem)],
+            kind: Kind::Reference {
+                lifetime: lifetime.as_ref().map(ToString::to_string),
+                mutable: mutability.is_some(),
+            },
+            empty_generics: false,
+        }),
+        syn::Type::Array(syn::TypeArray { elem, len, .. }) => Some(Type::array(
+            split_name_and_generic(elem),
+            tidy_tokens(&quote::quote! { #len }.to_string()),
+        )),
+        syn::Type::Slice(syn::TypeSlice { elem, .. }) => Some(Type::slice(split_name_and_generic(elem))),
+        syn::Type::Tuple(syn::TypeTuple { elems, .. }) => Some(Type::tuple(elems.iter().map(split_name_and_generic))),
+        _ => None,
+    };
+
+    // this isn't correct, but properly parsing the full AST is too tedious and abandoning early here is good enough
+    parsed.unwrap_or_else(|| Type {
+        name: tidy_tokens(&quote::quote! { #ast }.to_string()),
+        generics: vec![],
+        kind: Kind::Path,
+        empty_generics: false,
+    })
+}
+
+/// Removes the spaces `quote` puts between all tokens where Rust code
+/// usually has none, e.g. `Fn (u8 , u16) -> u8` becomes `Fn(u8, u16) -> u8`.
+fn tidy_tokens(tokens: &str) -> String {
+    let mut ret = String::with_capacity(tokens.len());
+    let mut prev = "";
+
+    for token in tokens.split_whitespace() {
+        let glued = prev.is_empty()
+            || prev == "::"
+            || token == "::"
+            || prev.ends_with(['(', '[', '<', '&'])
+            || (prev == "*" && (token == "const" || token == "mut"))
+            || token.starts_with([',', ';', ')', ']', '>'])
+            || (token.starts_with(['(', '<'])
+                && prev.ends_with(|c: char| c.is_alphanumeric() || c == '_'));
+
+        if !glued {
+            ret.push(' ');
+        }
+        ret.push_str(token);
+        prev = token;
+    }
+
+    ret
+}
+
+fn split_path(path: &syn::Path) -> Option<Type> {
+    let segments = &path.segments;
+    let base_type = segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<String>>().join("::");
+    let mut new_type = Type::new(&base_type);
+
+    match &segments.last().unwrap().arguments {
+        syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }) => {
+            for arg in args.iter() {
+                match arg {
+                    syn::GenericArgument::Type(t) => {
+                        new_type.generic(split_name_and_generic(t));
                     }
+                    _ => return None,
                 }
-            };
-            new_type
-        }
-        _ => {
-            // this isn't correct, but properly parsing the full AST is too tedious and abandoning early here is good enough
-            Type {
-                name: quote::quote! { #ast }.to_string(),
-                generics: vec![]
             }
-        },
-    }
+        }
+        // function traits such as `Fn(u8) -> u8` are kept as they are
+        syn::PathArguments::Parenthesized(_) => return None,
+        syn::PathArguments::None => {}
+    };
+
+    Some(new_type)
+}
+
+fn split_bounds<P>(bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, P>) -> Option<Vec<Type>> {
+    bounds
+        .iter()
+        .map(|bound| match bound {
+            syn::TypeParamBound::Trait(syn::TraitBound {
+                paren_token: None,
+                modifier: syn::TraitBoundModifier::None,
+                lifetimes: None,
+                path,
+            }) => split_path(path),
+            syn::TypeParamBound::Lifetime(lifetime) => Some(Type::new(lifetime)),
+            _ => None,
+        })
+        .collect()
 }
+
 impl Type {
     /// Return a new type with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is malformed, see [`Type::new_checked`].
     pub fn new(name: impl ToString) -> Self {
+        Type::new_checked(name).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Return a new type with the given name, or an error if the name is
+    /// empty, has unbalanced angle brackets or its generics fail to parse.
+    pub fn new_checked(name: impl ToString) -> Result<Self, TypeParseError> {
         let name = name.to_string();
-        if name.contains('<') {
-            split_name_and_generic(&syn::parse_str(&name).unwrap())
+        if name.trim().is_empty() {
+            return Err(TypeParseError::new(&name, "empty name"));
+        }
+
+        let mut depth = 0usize;
+        let mut prev = None;
+        for c in name.chars() {
+            match c {
+                '<' => depth += 1,
+                // `->` of function types is not a closing bracket
+                '>' if prev != Some('-') => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| TypeParseError::new(&name, "unexpected `>`"))?;
+                }
+                _ => {}
+            }
+            prev = Some(c);
+        }
+        if depth != 0 {
+            return Err(TypeParseError::new(&name, "unclosed `<`"));
+        }
+
+        if name.contains('<') || name.starts_with(['&', '[', '(']) {
+            let ast = syn::parse_str(&name).map_err(|err| TypeParseError::new(&name, err))?;
+            Ok(split_name_and_generic(&ast))
         } else {
-            Type {
-                name,
+            Ok(Type {
+                name: name.trim().to_string(),
                 generics: Vec::new(),
-            }
+                kind: Kind::Path,
+                empty_generics: false,
+            })
         }
     }
 
+    /// Return a new tuple type with the given elements.
+    ///
+    /// A single element tuple is rendered with a trailing comma, e.g. `(T,)`.
+    pub fn tuple<T>(tys: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type {
+            name: String::new(),
+            generics: tys.into_iter().map(Into::into).collect(),
+            kind: Kind::Tuple,
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new `impl Trait` type with the given bounds, e.g.
+    /// `impl Into<String>` or `impl Fn() + Send`.
+    pub fn impl_trait<T>(bounds: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type {
+            name: String::new(),
+            generics: bounds.into_iter().map(Into::into).collect(),
+            kind: Kind::ImplTrait,
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new trait object type with the given bounds, e.g.
+    /// `dyn Error` or `dyn Fn() + Send + 'static`.
+    pub fn dyn_trait<T>(bounds: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type {
+            name: String::new(),
+            generics: bounds.into_iter().map(Into::into).collect(),
+            kind: Kind::DynTrait,
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new shared reference to `ty` with an optional lifetime,
+    /// e.g. `&T` or `&'a T`.
+    pub fn reference<T>(ty: T, lifetime: Option<&str>) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type::new_reference(ty.into(), lifetime, false)
+    }
+
+    /// Return a new mutable reference to `ty` with an optional lifetime,
+    /// e.g. `&mut T` or `&'a mut T`.
+    pub fn mut_reference<T>(ty: T, lifetime: Option<&str>) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type::new_reference(ty.into(), lifetime, true)
+    }
+
+    fn new_reference(ty: Type, lifetime: Option<&str>, mutable: bool) -> Self {
+        Type {
+            name: String::new(),
+            generics: vec![ty],
+            kind: Kind::Reference {
+                lifetime: lifetime.map(ToString::to_string),
+                mutable,
+            },
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new array type of `len` elements of type `ty`, e.g.
+    /// `[u8; 4]`.
+    pub fn array<T>(ty: T, len: impl ToString) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type {
+            name: String::new(),
+            generics: vec![ty.into()],
+            kind: Kind::Array { len: len.to_string() },
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new slice type of elements of type `ty`, e.g. `[u8]`.
+    ///
+    /// Combine with [`Type::reference`] for a slice reference, e.g. `&[u8]`.
+    pub fn slice<T>(ty: T) -> Self
+    where
+        T: Into<Type>,
+    {
+        Type {
+            name: String::new(),
+            generics: vec![ty.into()],
+            kind: Kind::Slice,
+            empty_generics: false,
+        }
+    }
+
+    /// Return a new projection of the associated type `assoc` of `base`,
+    /// e.g. `Self::Output` or `Iterator::Item`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` has generics, as the shorthand form cannot be used
+    /// with them.
+    pub fn projection<T>(base: T, assoc: &str) -> Self
+    where
+        T: Into<Type>,
+    {
+        let base = base.into();
+        assert!(
+            base.kind == Kind::Path && base.generics.is_empty(),
+            "projections of generic types must use the qualified form"
+        );
+
+        Type::new(format!("{}::{}", base.name, assoc))
+    }
+
     /// Returns the name of the type
+    ///
+    /// For references, this is the name of the referenced type.
     pub fn name(&self) -> &String {
-        &self.name
+        match self.kind {
+            Kind::Reference { .. } => self.generics[0].name(),
+            _ => &self.name,
+        }
     }
 
-    /// Returns the name of the type
+    /// Returns the generics of the type
+    ///
+    /// For references, these are the generics of the referenced type.
     pub fn generics(&self) -> &Vec<Type> {
-        &self.generics
+        match self.kind {
+            Kind::Reference { .. } => self.generics[0].generics(),
+            _ => &self.generics,
+        }
     }
 
     /// Returns the key for sorting
     pub fn key_for_sorting(&self) -> &str {
-        match self.name.rfind("::") {
-            Some(index) => &self.name[index + 2..],
-            None => &self.name,
+        let name = self.name();
+        match name.rfind("::") {
+            Some(index) => &name[index + 2..],
+            None => name,
         }
     }
 
@@ -88,6 +414,20 @@ impl Type {
         self
     }
 
+    /// Insert a generic at `index`.
+    pub(crate) fn insert_generic(&mut self, index: usize, ty: Type) {
+        self.generics.insert(index, ty);
+    }
+
+    /// Set whether empty angle brackets are emitted when the type has no
+    /// generics, e.g. `Foo<>`.
+    ///
+    /// By default the brackets are omitted.
+    pub fn empty_generics(&mut self, emit: bool) -> &mut Self {
+        self.empty_generics = emit;
+        self
+    }
+
     /// Rewrite the `Type` with the provided path
     ///
     /// TODO: Is this needed?
@@ -102,13 +442,173 @@ impl Type {
         Type {
             name,
             generics: self.generics.clone(),
+            kind: self.kind.clone(),
+            empty_generics: self.empty_generics,
+        }
+    }
+
+    /// Returns a new `Type` with the path stripped from the name of this type
+    /// and, recursively, from the names of all of its generics, e.g.
+    /// `std::vec::Vec<foo::Bar>` becomes `Vec<Bar>`.
+    pub fn short_name(&self) -> Type {
+        self.map_names(|name| match name.rfind("::") {
+            Some(index) => name[index + 2..].to_string(),
+            None => name.to_string(),
+        })
+    }
+
+    /// Returns a new `Type` with the root path segment `from` replaced by
+    /// `to` in the name of this type and, recursively, in the names of all
+    /// of its generics, e.g. `std::fmt::Result` becomes `core::fmt::Result`.
+    ///
+    /// Single segment names are left untouched.
+    pub fn rewrite_root(&self, from: &str, to: &str) -> Type {
+        self.map_names(|name| match name.split_once("::") {
+            Some((root, rest)) if root == from => format!("{}::{}", to, rest),
+            _ => name.to_string(),
+        })
+    }
+
+    /// Returns a new `Type` with `f` applied to the name of this type and,
+    /// recursively, to the names of all of its generics.
+    ///
+    /// `Self` (including paths starting with `Self::`) and primitive types
+    /// are left untouched.
+    pub fn map_names(&self, f: impl Fn(&str) -> String) -> Type {
+        self.map_names_dyn(&f)
+    }
+
+    fn map_names_dyn(&self, f: &dyn Fn(&str) -> String) -> Type {
+        let name = match self.kind {
+            Kind::Path if !is_self_or_primitive(&self.name) => f(&self.name),
+            _ => self.name.clone(),
+        };
+
+        Type {
+            name,
+            generics: self.generics.iter().map(|ty| ty.map_names_dyn(f)).collect(),
+            kind: self.kind.clone(),
+            empty_generics: self.empty_generics,
+        }
+    }
+
+    /// Calls `f` on this type and, recursively, on all of its generics.
+    ///
+    /// Types are visited depth-first, parents before their generics.
+    pub fn for_each(&self, mut f: impl FnMut(&Type)) {
+        self.for_each_dyn(&mut f);
+    }
+
+    fn for_each_dyn(&self, f: &mut dyn FnMut(&Type)) {
+        f(self);
+        for ty in &self.generics {
+            ty.for_each_dyn(f);
         }
     }
 
     /// Formats the struct using the given formatter.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
-        write!(fmt, "{}", self.name)?;
-        Type::fmt_slice(&self.generics, fmt)
+        match self.kind {
+            Kind::Path => {
+                let name = fmt.type_name(&self.name).to_string();
+                write!(fmt, "{}", name)?;
+
+                if self.generics.is_empty() && self.empty_generics {
+                    write!(fmt, "<>")
+                } else {
+                    Type::fmt_slice(&self.generics, fmt)
+                }
+            }
+            Kind::Tuple => {
+                write!(fmt, "(")?;
+
+                for (i, ty) in self.generics.iter().enumerate() {
+                    if i != 0 {
+                        write!(fmt, ", ")?
+                    }
+                    ty.fmt(fmt)?;
+                }
+
+                if self.generics.len() == 1 {
+                    write!(fmt, ",")?;
+                }
+
+                write!(fmt, ")")
+            }
+            Kind::ImplTrait | Kind::DynTrait => {
+                if self.kind == Kind::ImplTrait {
+                    write!(fmt, "impl ")?;
+                } else {
+                    write!(fmt, "dyn ")?;
+                }
+
+                for (i, ty) in self.generics.iter().enumerate() {
+                    if i != 0 {
+                        write!(fmt, " + ")?
+                    }
+                    ty.fmt(fmt)?;
+                }
+
+                Ok(())
+            }
+            Kind::Reference { ref lifetime, mutable } => {
+                write!(fmt, "&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(fmt, "{} ", lifetime)?;
+                }
+                if mutable {
+                    write!(fmt, "mut ")?;
+                }
+
+                // `&dyn A + B` is ambiguous, the bounds need parentheses
+                let inner = &self.generics[0];
+                if matches!(inner.kind, Kind::ImplTrait | Kind::DynTrait) && inner.generics.len() > 1 {
+                    write!(fmt, "(")?;
+                    inner.fmt(fmt)?;
+                    write!(fmt, ")")
+                } else {
+                    inner.fmt(fmt)
+                }
+            }
+            Kind::Array { ref len } => {
+                write!(fmt, "[")?;
+                self.generics[0].fmt(fmt)?;
+                write!(fmt, "; {}]", len)
+            }
+            Kind::Slice => {
+                write!(fmt, "[")?;
+                self.generics[0].fmt(fmt)?;
+                write!(fmt, "]")
+            }
+        }
+    }
+
+    /// Formats the type for an expression, e.g. a call site, using the
+    /// turbofish form `Vec::<u8>` for the generics of a named type.
+    ///
+    /// Other types are wrapped in angle brackets, e.g. `<[u8; 4]>`, so that
+    /// they can be followed by `::`.
+    pub fn fmt_turbofish(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Path => {
+                let name = fmt.type_name(&self.name).to_string();
+                write!(fmt, "{}", name)?;
+
+                if self.generics.is_empty() && self.empty_generics {
+                    write!(fmt, "::<>")
+                } else {
+                    if !self.generics.is_empty() {
+                        write!(fmt, "::")?;
+                    }
+                    Type::fmt_slice(&self.generics, fmt)
+                }
+            }
+            _ => {
+                write!(fmt, "<")?;
+                self.fmt(fmt)?;
+                write!(fmt, ">")
+            }
+        }
     }
 
     fn fmt_slice(generics: &[Type], fmt: &mut Formatter<'_>) -> fmt::Result {
@@ -150,6 +650,26 @@ fn parse_type() {
     }
 }
 
+#[test]
+fn parse_normalizes_whitespace() {
+    let cases = [
+        ("Vec< u8 >", "Vec<u8>"),
+        (" u8 ", "u8"),
+        ("HashMap < String ,Vec<u8> >", "HashMap<String, Vec<u8>>"),
+        ("Box<dyn Fn (u8 ,u16)->u8 >", "Box<dyn Fn(u8, u16) -> u8>"),
+        ("Option<fn(& str) -> std :: io :: Result< () >>", "Option<fn(&str) -> std::io::Result<()>>"),
+        ("Vec<* const u8>", "Vec<*const u8>"),
+        ("Vec<<T as Trait>::Output>", "Vec<<T as Trait>::Output>"),
+        ("[u8 ; N * 2]", "[u8; N * 2]"),
+    ];
+
+    for (input, expected) in cases {
+        let mut ret = String::new();
+        Type::new(input).fmt(&mut Formatter::new(&mut ret)).unwrap();
+        assert_eq!(ret, expected, "input: {:?}", input);
+    }
+}
+
 #[test]
 fn parse_generic() {
     {
@@ -184,6 +704,276 @@ fn parse_generic() {
     {
         let ty = Type::new("Result<&'a mut Foo<Bar>>");
         assert_eq!(ty.name, "Result");
-        assert_eq!(ty.generics.iter().map(|generic| generic.name().as_str()).collect::<Vec<&str>>().join(" "), "& 'a mut Foo < Bar >");
+        assert_eq!(ty.generics.iter().map(|generic| generic.name().as_str()).collect::<Vec<&str>>().join(" "), "Foo");
+
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        assert_eq!(ret, "Result<&'a mut Foo<Bar>>");
+    }
+}
+#[test]
+fn map_names() {
+    let ty = Type::new("HashMap<String, Vec<foo::Bar>>").map_names(|name| name.to_uppercase());
+
+    let mut ret = String::new();
+    ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "HASHMAP<STRING, VEC<FOO::BAR>>");
+}
+
+#[test]
+fn for_each() {
+    let ty = Type::new("HashMap<String, Vec<u8>>");
+
+    let mut names = vec![];
+    ty.for_each(|ty| names.push(ty.name().clone()));
+    assert_eq!(names, ["HashMap", "String", "Vec", "u8"]);
+}
+
+#[test]
+fn tuple() {
+    let fmt_type = |ty: Type| {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        ret
+    };
+
+    assert_eq!(fmt_type(Type::tuple(Vec::<Type>::new())), "()");
+    assert_eq!(fmt_type(Type::tuple(["u8"])), "(u8,)");
+    assert_eq!(fmt_type(Type::tuple(["u8", "Vec<String>"])), "(u8, Vec<String>)");
+}
+
+#[test]
+fn empty_generics() {
+    let mut ty = Type::new("Foo");
+
+    let mut ret = String::new();
+    ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "Foo");
+
+    let mut ret = String::new();
+    ty.empty_generics(true).fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "Foo<>");
+}
+
+#[test]
+fn short_name() {
+    let fmt_type = |ty: Type| {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        ret
+    };
+
+    assert_eq!(fmt_type(Type::new("std::vec::Vec<foo::Bar>").short_name()), "Vec<Bar>");
+    assert_eq!(fmt_type(Type::new("Self").short_name()), "Self");
+    assert_eq!(fmt_type(Type::new("Self::Output").short_name()), "Self::Output");
+    assert_eq!(fmt_type(Type::new("u8").short_name()), "u8");
+}
+
+#[test]
+fn rewrite_root() {
+    let fmt_type = |ty: Type| {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        ret
+    };
+
+    assert_eq!(
+        fmt_type(Type::new("std::result::Result<Self, std::fmt::Error>").rewrite_root("std", "core")),
+        "core::result::Result<Self, core::fmt::Error>"
+    );
+    assert_eq!(fmt_type(Type::new("std").rewrite_root("std", "core")), "std");
+    assert_eq!(fmt_type(Type::new("Self").rewrite_root("Self", "Foo")), "Self");
+    assert_eq!(fmt_type(Type::new("u8").map_names(|name| format!("foo::{}", name))), "u8");
+}
+
+#[test]
+fn parse_impl_trait() {
+    let ty = Type::new("impl Into<String> + Send + 'static");
+
+    let mut ret = String::new();
+    ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "impl Into<String> + Send + 'static");
+    assert_eq!(ty.generics.iter().map(|generic| generic.name().as_str()).collect::<Vec<&str>>(), ["Into", "Send", "'static"]);
+}
+
+#[test]
+fn projection() {
+    let mut ret = String::new();
+    Type::projection("Self", "Output").fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "Self::Output");
+
+    let mut ret = String::new();
+    Type::projection(Type::new("Iterator"), "Item").fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "Iterator::Item");
+}
+
+#[test]
+fn reference() {
+    for (ty, expected) in [
+        (Type::reference("T", None), "&T"),
+        (Type::mut_reference("T", None), "&mut T"),
+        (Type::reference("T", Some("'a")), "&'a T"),
+        (Type::mut_reference("Vec<T>", Some("'a")), "&'a mut Vec<T>"),
+        (Type::new("Option<&'static str>"), "Option<&'static str>"),
+        (Type::new("&str"), "&str"),
+        (Type::new("&'a mut [u8]"), "&'a mut [u8]"),
+    ] {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        assert_eq!(ret, expected);
+    }
+
+    let ty = Type::new("&'a mut HashMap<String, u8>");
+    assert_eq!(ty.name(), "HashMap");
+    assert_eq!(ty.key_for_sorting(), "HashMap");
+    assert_eq!(ty.generics().len(), 2);
+}
+
+#[test]
+fn parse_multiple_generics() {
+    for (name, generics) in [
+        ("Map<K, V>", vec!["K", "V"]),
+        ("Result<T, E>", vec!["T", "E"]),
+        ("HashMap<String, Vec<u8>>", vec!["String", "Vec<u8>"]),
+        ("Vec<HashMap<String, Vec<u8>>>", vec!["HashMap<String, Vec<u8>>"]),
+    ] {
+        let ty = Type::new(name);
+        let rendered = ty
+            .generics
+            .iter()
+            .map(|generic| {
+                let mut ret = String::new();
+                generic.fmt(&mut Formatter::new(&mut ret)).unwrap();
+                ret
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rendered, generics);
+    }
+
+    let ty = Type::new("Vec<HashMap<String, Vec<u8>>>");
+    let map = &ty.generics[0];
+    assert_eq!(map.name, "HashMap");
+    assert_eq!(map.generics[1].name, "Vec");
+    assert_eq!(map.generics[1].generics[0].name, "u8");
+}
+
+#[test]
+fn new_checked() {
+    assert_eq!(Type::new_checked("Vec<u8>"), Ok(Type::new("Vec<u8>")));
+    assert!(Type::new_checked("Box<dyn Fn(u8) -> u8>").is_ok());
+
+    for (name, reason) in [
+        ("", "empty name"),
+        ("  ", "empty name"),
+        ("Vec<u8", "unclosed `<`"),
+        ("Vec<u8>>", "unexpected `>`"),
+    ] {
+        let err = Type::new_checked(name).unwrap_err();
+        assert_eq!(err.input(), name);
+        assert_eq!(err.reason(), reason);
+    }
+
+    let err = Type::new_checked("Vec<u8 u8>").unwrap_err();
+    assert_eq!(err.to_string(), "malformed type `Vec<u8 u8>`: expected `,`");
+}
+
+#[test]
+fn array() {
+    for (ty, expected) in [
+        (Type::array("u8", 4), "[u8; 4]"),
+        (Type::new("[u8; 4]"), "[u8; 4]"),
+        (Type::new("[Vec<u8>; N]"), "[Vec<u8>; N]"),
+    ] {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        assert_eq!(ret, expected);
+    }
+}
+
+#[test]
+fn structural_equality() {
+    use std::collections::HashSet;
+
+    assert_eq!(Type::new("Vec<u8>"), Type::new("Vec<u8>"));
+    assert_ne!(Type::new("Vec<u8>"), Type::new("Vec<u16>"));
+    assert_ne!(Type::new("HashMap<K, V>"), Type::new("HashMap<V, K>"));
+    assert_ne!(Type::new("&u8"), Type::new("&mut u8"));
+
+    let mut ty = Type::new("Vec");
+    ty.generic("u8");
+    assert_eq!(ty, Type::new("Vec<u8>"));
+
+    let set = [Type::new("Vec<u8>"), ty, Type::new("Vec<u16>")].into_iter().collect::<HashSet<_>>();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn turbofish() {
+    let ty = Type::new("Vec<u8>");
+
+    let mut ret = String::new();
+    ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+    assert_eq!(ret, "Vec<u8>");
+
+    for (input, expect) in [
+        ("Vec<u8>", "Vec::<u8>"),
+        ("HashMap<String, Vec<u8>>", "HashMap::<String, Vec<u8>>"),
+        ("String", "String"),
+        ("[u8; 4]", "<[u8; 4]>"),
+    ] {
+        let mut ret = String::new();
+        Type::new(input).fmt_turbofish(&mut Formatter::new(&mut ret)).unwrap();
+        assert_eq!(ret, expect);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn slice_array_and_tuple() {
+    fn render(ty: &Type) -> String {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        ret
+    }
+
+    assert_eq!(render(&Type::slice("u8")), "[u8]");
+    assert_eq!(render(&Type::reference(Type::slice("T"), Some("'a"))), "&'a [T]");
+    assert_eq!(render(&Type::array("u8", 32)), "[u8; 32]");
+    assert_eq!(render(&Type::tuple(["A", "B"])), "(A, B)");
+
+    let mut vec = Type::new("Vec");
+    vec.generic(Type::array("u8", 16));
+    assert_eq!(render(&vec), "Vec<[u8; 16]>");
+
+    // parsed types store the same structure
+    assert_eq!(Type::new("[u8]"), Type::slice("u8"));
+    assert_eq!(Type::new("&[T]"), Type::reference(Type::slice("T"), None));
+    assert_eq!(Type::new("(A, B)"), Type::tuple(["A", "B"]));
+    assert_eq!(Type::new("Vec<[u8; 16]>"), vec);
+    assert_eq!(Type::new("HashMap<(u8, u16), [u8]>").generics()[0], Type::tuple(["u8", "u16"]));
+    assert_eq!(Type::new("()"), Type::tuple(Vec::<Type>::new()));
+}
+
+#[test]
+fn trait_bound_types() {
+    fn render(ty: &Type) -> String {
+        let mut ret = String::new();
+        ty.fmt(&mut Formatter::new(&mut ret)).unwrap();
+        ret
+    }
+
+    let mut boxed = Type::new("Box");
+    boxed.generic(Type::dyn_trait(["Error"]));
+    assert_eq!(render(&boxed), "Box<dyn Error>");
+    assert_eq!(Type::new("Box<dyn Error>"), boxed);
+
+    let iter = Type::impl_trait(["Iterator<Item = u8>"]);
+    assert_eq!(render(&iter), "impl Iterator<Item = u8>");
+
+    let object = Type::dyn_trait(["A", "Send", "'static"]);
+    assert_eq!(render(&object), "dyn A + Send + 'static");
+    assert_eq!(Type::new("Box<dyn A + Send + 'static>").generics()[0], object);
+
+    let reference = Type::reference(object, None);
+    assert_eq!(render(&reference), "&(dyn A + Send + 'static)");
+    assert_eq!(Type::new("&(dyn A + Send + 'static)"), reference);
+}
@@ -2,9 +2,10 @@ use core::fmt;
 use std::fmt::Write;
 
 use crate::{type_def::TypeDef, Formatter, Type};
+use crate::visibility::Visibility;
 
 /// https://rust-lang.github.io/chalk/book/types/rust_types/alias.html#alias-types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeAlias {
     type_def: TypeDef,
     ty: Type,
@@ -24,7 +25,7 @@ impl TypeAlias {
         self.type_def.fmt_head("type", &[], fmt)?;
         write!(fmt, " = ")?;
         self.ty.fmt(fmt)?;
-        write!(fmt, ";\n")?;
+        writeln!(fmt, ";")?;
         Ok(())
     }
 
@@ -34,11 +35,22 @@ impl TypeAlias {
     }
 
     /// Set the TypeAliasure visibility.
-    pub fn vis(&mut self, vis: impl ToString) -> &mut Self {
-        self.type_def.vis(vis);
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
         self
     }
 
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
     /// Add a generic to the TypeAlias.
     pub fn generic(&mut self, name: impl ToString) -> &mut Self {
         self.type_def.ty.generic(name);
@@ -46,6 +58,8 @@ impl TypeAlias {
     }
 
     /// Add a `where` bound to the TypeAlias.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
     pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
         T: Into<Type>,
@@ -54,12 +68,25 @@ impl TypeAlias {
         self
     }
 
+    /// Sort the `where` clause predicates by name when formatting the TypeAlias.
+    pub fn sort_bounds(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_bounds(sort);
+        self
+    }
+
     /// Set the TypeAliasure documentation.
     pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
         self.type_def.doc(docs);
         self
     }
 
+    /// Add a `#[doc = <expr>]` attribute whose value is an expression rather
+    /// than a string literal, e.g. `concat!("Since ", env!("CARGO_PKG_VERSION"))`.
+    pub fn doc_expr(&mut self, expr: impl ToString) -> &mut Self {
+        self.type_def.doc_expr(expr);
+        self
+    }
+
     /// Add a new type that the TypeAlias should derive.
     pub fn derive(&mut self, name: impl ToString) -> &mut Self {
         self.type_def.derive(name);
@@ -78,6 +105,12 @@ impl TypeAlias {
         self
     }
 
+    /// Add a `cfg` attribute to conditionally compile the type alias.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
     /// Set the type alias's ty.
     pub fn set_ty(&mut self, ty: Type) {
         self.ty = ty;
@@ -1,24 +1,32 @@
 use std::fmt::{self, Write};
 
-use crate::bound::Bound;
+use crate::bound::{push_bound, Bound};
 use crate::docs::Docs;
-use crate::formatter::{fmt_bounds, Formatter};
+use crate::formatter::{fmt_bounds_ending, Formatter};
+use crate::r#impl::Impl;
+use crate::visibility::Visibility;
 
-use crate::r#type::Type;
+use crate::r#type::{lifetime_name, Type};
 
 /// Defines a type definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeDef {
     pub ty: Type,
     vis: Option<String>,
     docs: Option<Docs>,
+    doc_exprs: Vec<String>,
     derive: Vec<String>,
+    sort_derives: bool,
+    split_derives: bool,
     allow: Vec<String>,
     attributes: Vec<String>,
     repr: Option<String>,
+    cfg: Vec<String>,
     bounds: Vec<Bound>,
+    sort_bounds: bool,
     macros: Vec<String>,
     cfg_attrs: Vec<String>,
+    skip_rustfmt: bool,
 }
 
 impl TypeDef {
@@ -28,28 +36,67 @@ impl TypeDef {
             ty: Type::new(name),
             vis: None,
             docs: None,
+            doc_exprs: Vec::new(),
             derive: Vec::new(),
+            sort_derives: false,
+            split_derives: false,
             allow: Vec::new(),
             attributes: Vec::new(),
             repr: None,
+            cfg: Vec::new(),
             bounds: Vec::new(),
+            sort_bounds: false,
             macros: Vec::new(),
             cfg_attrs: Vec::new(),
+            skip_rustfmt: false,
         }
     }
 
-    pub fn vis(&mut self, vis: impl ToString) {
-        self.vis = Some(vis.to_string());
+    pub fn vis(&mut self, vis: Visibility) {
+        self.vis = vis.into_modifier();
+    }
+
+    pub fn default_vis(&mut self, vis: &str) {
+        self.vis.get_or_insert_with(|| vis.to_string());
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.vis.is_some()
+    }
+
+    /// Add a generic to the type, unless it already has an equal one.
+    pub fn generic(&mut self, name: impl ToString) {
+        let generic = Type::new(name);
+        if !self.ty.generics().contains(&generic) {
+            self.ty.generic(generic);
+        }
+    }
+
+    /// Add a lifetime to the type, after the lifetimes but before the other
+    /// generics already added.
+    pub fn lifetime(&mut self, name: impl ToString) {
+        let lifetime = Type::new(lifetime_name(name));
+        if !self.ty.generics().contains(&lifetime) {
+            let index = self
+                .ty
+                .generics()
+                .iter()
+                .take_while(|generic| generic.name().starts_with('\''))
+                .count();
+            self.ty.insert_generic(index, lifetime);
+        }
     }
 
     pub fn bound<T>(&mut self, name: impl ToString, ty: T)
     where
         T: Into<Type>,
     {
-        self.bounds.push(Bound {
-            name: name.to_string(),
-            bound: vec![ty.into()],
-        });
+        push_bound(&mut self.bounds, name.to_string(), ty.into());
+    }
+
+    pub fn sort_bounds(&mut self, sort: bool) {
+        self.sort_bounds = sort;
     }
 
     pub fn r#macro(&mut self, r#macro: impl ToString) {
@@ -60,12 +107,54 @@ impl TypeDef {
         self.attributes.push(attr.to_string());
     }
 
+    /// Returns the case set by a `#[serde(rename_all = "...")]` attribute,
+    /// if any.
+    pub(crate) fn serde_rename_all(&self) -> Option<&str> {
+        self.attributes.iter().find_map(|attr| {
+            attr.strip_prefix("serde(rename_all = \"")
+                .and_then(|rest| rest.strip_suffix("\")"))
+        })
+    }
+
+    /// Emit a `#[rustfmt::skip]` attribute, preserving the layout of the
+    /// definition.
+    pub fn skip_rustfmt(&mut self) {
+        self.skip_rustfmt = true;
+    }
+
     pub fn doc(&mut self, docs: impl ToString) {
         self.docs = Some(Docs::new(docs));
     }
 
+    /// Add a `#[doc = <expr>]` attribute whose value is an expression, e.g.
+    /// a `concat!` invocation, emitted unquoted.
+    pub fn doc_expr(&mut self, expr: impl ToString) {
+        self.doc_exprs.push(expr.to_string());
+    }
+
+    /// Add a derive, unless it was already added.
     pub fn derive(&mut self, name: impl ToString) {
-        self.derive.push(name.to_string());
+        let name = name.to_string();
+        if !self.has_derive(&name) {
+            self.derive.push(name);
+        }
+    }
+
+    /// Sort the derives alphabetically when formatting, instead of keeping
+    /// the order in which they were added.
+    pub fn sort_derives(&mut self, sort: bool) {
+        self.sort_derives = sort;
+    }
+
+    /// Emit each derive as its own `#[derive(..)]` attribute, instead of a
+    /// single combined list.
+    pub fn split_derives(&mut self, split: bool) {
+        self.split_derives = split;
+    }
+
+    /// Returns `true` if `name` is one of the derived traits.
+    pub fn has_derive(&self, name: &str) -> bool {
+        self.derive.iter().any(|derive| derive == name)
     }
 
     pub fn allow(&mut self, allow: impl ToString) {
@@ -76,26 +165,70 @@ impl TypeDef {
         self.repr = Some(repr.to_string());
     }
 
+    /// Returns `true` if `repr` is one of the representation hints.
+    pub fn has_repr(&self, repr: &str) -> bool {
+        self.repr
+            .as_ref()
+            .is_some_and(|hints| hints.split(',').any(|hint| hint.trim() == repr))
+    }
+
+    pub fn cfg(&mut self, cfg: impl ToString) {
+        self.cfg.push(cfg.to_string());
+    }
+
     pub fn cfg_attr(&mut self, cfg_attr: impl ToString) {
         self.cfg_attrs.push(cfg_attr.to_string());
     }
 
+    pub fn cfg_attr_with(&mut self, cond: impl ToString, content: impl ToString) {
+        self.cfg_attrs.push(format!(
+            "{}, {}",
+            cond.to_string().trim(),
+            content.to_string().trim()
+        ));
+    }
+
+    /// Formats the head of the type definition.
+    ///
+    /// Attributes are emitted in a fixed order: docs, doc expressions, `cfg`,
+    /// `allow`, `derive`, `cfg_attr`, `repr`, other attributes,
+    /// `rustfmt::skip` and finally macros.
     pub fn fmt_head(
         &self,
         keyword: &str,
         parents: &[Type],
         fmt: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        self.fmt_head_without_bounds(keyword, parents, fmt)?;
+        self.fmt_bounds_ending(",", fmt)
+    }
+
+    /// Formats the head of the type definition without its `where` clause,
+    /// for definitions where the clause follows the body.
+    pub fn fmt_head_without_bounds(
+        &self,
+        keyword: &str,
+        parents: &[Type],
+        fmt: &mut Formatter<'_>,
     ) -> fmt::Result {
         if let Some(ref docs) = self.docs {
             docs.fmt(fmt)?;
         }
 
+        for expr in &self.doc_exprs {
+            writeln!(fmt, "#[doc = {}]", expr)?;
+        }
+
+        self.fmt_cfg(fmt)?;
         self.fmt_allow(fmt)?;
         self.fmt_derive(fmt)?;
+        self.fmt_cfg_attrs(fmt)?;
         self.fmt_repr(fmt)?;
         self.fmt_attributes(fmt)?;
+        if self.skip_rustfmt {
+            writeln!(fmt, "#[rustfmt::skip]")?;
+        }
         self.fmt_macros(fmt)?;
-        self.fmt_cfg_attrs(fmt)?;
 
         if let Some(ref vis) = self.vis {
             write!(fmt, "{} ", vis)?;
@@ -116,14 +249,56 @@ impl TypeDef {
             }
         }
 
-        fmt_bounds(&self.bounds, fmt)?;
-
         Ok(())
     }
 
+    /// Returns an impl block of `trait_name` for the type, with the generics
+    /// and `where` bounds of the definition and each type parameter bounded
+    /// by the trait, e.g. `impl<'a, T> Default for Foo<'a, T> where T: Default`.
+    pub(crate) fn impl_trait_for(&self, trait_name: &str) -> Impl {
+        let mut imp = Impl::new(&self.ty);
+        for generic in self.ty.generics() {
+            imp.generic(generic.name());
+            if !generic.name().starts_with('\'') {
+                imp.bound(generic.name(), trait_name);
+            }
+        }
+        for bound in &self.bounds {
+            for ty in &bound.bound {
+                imp.bound(&bound.name, ty);
+            }
+        }
+        imp.impl_trait(trait_name);
+        imp
+    }
+
+    /// Returns `true` if the type definition has `where` bounds.
+    pub fn has_bounds(&self) -> bool {
+        !self.bounds.is_empty()
+    }
+
+    /// Formats the `where` clause, terminating the last predicate with `end`.
+    pub fn fmt_bounds_ending(&self, end: &str, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if self.sort_bounds {
+            let mut sorted = self.bounds.clone();
+            sorted.sort_by(|a, b| a.name.cmp(&b.name));
+            fmt_bounds_ending(&sorted, end, fmt)
+        } else {
+            fmt_bounds_ending(&self.bounds, end, fmt)
+        }
+    }
+
     fn fmt_attributes(&self, fmt: &mut Formatter) -> fmt::Result {
         for attr in &self.attributes {
-            write!(fmt, "#[{}]\n", attr)?;
+            writeln!(fmt, "#[{}]", attr)?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt_cfg(&self, fmt: &mut Formatter) -> fmt::Result {
+        for cfg in &self.cfg {
+            writeln!(fmt, "#[cfg({})]", cfg)?;
         }
 
         Ok(())
@@ -131,7 +306,7 @@ impl TypeDef {
 
     fn fmt_allow(&self, fmt: &mut Formatter) -> fmt::Result {
         for allow in &self.allow {
-            write!(fmt, "#[allow({})]\n", allow)?;
+            writeln!(fmt, "#[allow({})]", allow)?;
         }
 
         Ok(())
@@ -139,7 +314,7 @@ impl TypeDef {
 
     fn fmt_repr(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         if let Some(ref repr) = self.repr {
-            write!(fmt, "#[repr({})]\n", repr)?;
+            writeln!(fmt, "#[repr({})]", repr)?;
         }
 
         Ok(())
@@ -147,16 +322,31 @@ impl TypeDef {
 
     fn fmt_derive(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         if !self.derive.is_empty() {
-            write!(fmt, "#[derive(")?;
+            let mut derives = self.derive.iter().collect::<Vec<_>>();
+            if self.sort_derives {
+                derives.sort();
+            }
 
-            for (i, name) in self.derive.iter().enumerate() {
-                if i != 0 {
-                    write!(fmt, ", ")?
+            if self.split_derives {
+                for name in derives {
+                    writeln!(fmt, "#[derive({})]", name)?;
+                }
+            } else {
+                let list = derives.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ");
+
+                if fmt.exceeds_max_width("#[derive()]".len() + list.len()) {
+                    writeln!(fmt, "#[derive(")?;
+                    fmt.indent(|fmt| {
+                        for name in &derives {
+                            writeln!(fmt, "{},", name)?;
+                        }
+                        Ok::<_, fmt::Error>(())
+                    })?;
+                    writeln!(fmt, ")]")?;
+                } else {
+                    writeln!(fmt, "#[derive({})]", list)?;
                 }
-                write!(fmt, "{}", name)?;
             }
-
-            write!(fmt, ")]\n")?;
         }
 
         Ok(())
@@ -164,14 +354,14 @@ impl TypeDef {
 
     fn fmt_macros(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         for m in self.macros.iter() {
-            write!(fmt, "{}\n", m)?;
+            writeln!(fmt, "{}", m)?;
         }
         Ok(())
     }
 
     fn fmt_cfg_attrs(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         for attr in &self.cfg_attrs {
-            write!(fmt, "#[cfg_attr({})]\n", attr)?;
+            writeln!(fmt, "#[cfg_attr({})]", attr)?;
         }
 
         Ok(())
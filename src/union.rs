@@ -0,0 +1,172 @@
+use std::fmt;
+
+use crate::field::Field;
+use crate::fields::Fields;
+use crate::formatter::Formatter;
+use crate::type_def::TypeDef;
+use crate::visibility::Visibility;
+
+use crate::r#type::Type;
+
+/// Defines a union.
+///
+/// Unions usually need a representation, e.g. `#[repr(C)]`, which can be set
+/// with [`Union::repr`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Union {
+    type_def: TypeDef,
+
+    /// Union fields
+    fields: Fields,
+}
+
+impl Union {
+    /// Return a union definition with the provided name
+    pub fn new(name: impl ToString) -> Self {
+        Union {
+            type_def: TypeDef::new(name),
+            fields: Fields::Empty,
+        }
+    }
+
+    /// Returns a reference to the type
+    pub fn ty(&self) -> &Type {
+        &self.type_def.ty
+    }
+
+    /// Returns a reference to the union's fields.
+    pub fn fields(&self) -> &Fields {
+        &self.fields
+    }
+
+    /// Set the union visibility.
+    pub fn vis(&mut self, vis: impl Into<Visibility>) -> &mut Self {
+        self.type_def.vis(vis.into());
+        self
+    }
+
+    /// Set the visibility if none was set explicitly.
+    pub(crate) fn default_vis(&mut self, vis: &str) -> &mut Self {
+        self.type_def.default_vis(vis);
+        self
+    }
+
+    /// Returns `true` if a visibility was set explicitly.
+    pub(crate) fn has_vis(&self) -> bool {
+        self.type_def.has_vis()
+    }
+
+    /// Add a generic to the union.
+    ///
+    /// Generics that were already added are ignored.
+    pub fn generic(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.generic(name);
+        self
+    }
+
+    /// Add a lifetime to the union, e.g. `'a`.
+    pub fn lifetime(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.lifetime(name);
+        self
+    }
+
+    /// Add a `where` bound to the union.
+    ///
+    /// Bounds on the same name are joined, e.g. `T: Clone + Debug`.
+    pub fn bound<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.type_def.bound(name, ty);
+        self
+    }
+
+    /// Set the union documentation.
+    pub fn doc(&mut self, docs: impl ToString) -> &mut Self {
+        self.type_def.doc(docs);
+        self
+    }
+
+    /// Add a new type that the union should derive.
+    ///
+    /// Derives that were already added are ignored.
+    pub fn derive(&mut self, name: impl ToString) -> &mut Self {
+        self.type_def.derive(name);
+        self
+    }
+
+    /// Sort the derives alphabetically when formatting the union.
+    pub fn sort_derives(&mut self, sort: bool) -> &mut Self {
+        self.type_def.sort_derives(sort);
+        self
+    }
+
+    /// Emit each derive of the union as its own `#[derive(..)]` attribute.
+    pub fn split_derives(&mut self, split: bool) -> &mut Self {
+        self.type_def.split_derives(split);
+        self
+    }
+
+    /// Add a new attribute to the union
+    pub fn attr(&mut self, attr: impl ToString) -> &mut Self {
+        self.type_def.attr(attr);
+        self
+    }
+
+    /// Specify lint attribute to supress a warning or error.
+    pub fn allow(&mut self, allow: impl ToString) -> &mut Self {
+        self.type_def.allow(allow);
+        self
+    }
+
+    /// Specify representation, e.g. `C`.
+    pub fn repr(&mut self, repr: impl ToString) -> &mut Self {
+        self.type_def.repr(repr);
+        self
+    }
+
+    /// Add a `cfg` attribute to conditionally compile the union.
+    pub fn cfg(&mut self, cfg: impl ToString) -> &mut Self {
+        self.type_def.cfg(cfg);
+        self
+    }
+
+    /// Push a field to the union.
+    pub fn push_field(&mut self, field: Field) -> &mut Self {
+        self.fields.push_named(field);
+        self
+    }
+
+    /// Add a field to the union.
+    pub fn field<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
+    where
+        T: Into<Type>,
+    {
+        self.fields.named(name, ty);
+        self
+    }
+
+    /// Create a field for the union, returning a mutable reference to it.
+    pub fn new_field<T>(&mut self, name: impl ToString, ty: T) -> &mut Field
+    where
+        T: Into<Type>,
+    {
+        self.fields.new_named(name, ty)
+    }
+
+    /// Formats the union using the given formatter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the union has no fields.
+    pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        assert!(
+            !self.fields.is_empty(),
+            "union `{}` must have at least one field",
+            self.ty().name()
+        );
+
+        self.type_def.fmt_head("union", &[], fmt)?;
+        self.fields.fmt(fmt)
+    }
+}
@@ -1,17 +1,20 @@
 use std::fmt::{self, Write};
 
+use crate::field::Field;
 use crate::fields::Fields;
 use crate::formatter::Formatter;
 
 use crate::r#type::Type;
 
 /// Defines an enum variant.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Variant {
     name: String,
     fields: Fields,
     /// Annotations for field e.g., `#[serde(rename = "variant")]`.
     annotations: Vec<String>,
+    /// Explicit discriminant, e.g. `0xFF`
+    discriminant: Option<String>,
 }
 
 impl Variant {
@@ -21,9 +24,20 @@ impl Variant {
             name: name.to_string(),
             fields: Fields::Empty,
             annotations: Vec::new(),
+            discriminant: None,
         }
     }
 
+    /// Returns the name of the variant.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Returns a reference to the variant's fields.
+    pub fn fields(&self) -> &Fields {
+        &self.fields
+    }
+
     /// Add a named field to the variant.
     pub fn named<T>(&mut self, name: impl ToString, ty: T) -> &mut Self
     where
@@ -33,28 +47,137 @@ impl Variant {
         self
     }
 
+    /// Push a named field to the variant.
+    pub fn push_named(&mut self, field: Field) -> &mut Self {
+        self.fields.push_named(field);
+        self
+    }
+
+    /// Create a named field for the variant, returning a mutable reference to
+    /// it, e.g. to document it.
+    pub fn new_named<T>(&mut self, name: impl ToString, ty: T) -> &mut Field
+    where
+        T: Into<Type>,
+    {
+        self.fields.new_named(name, ty)
+    }
+
     /// Add a tuple field to the variant.
     pub fn tuple(&mut self, ty: impl ToString) -> &mut Self {
         self.fields.tuple(None, ty);
         self
     }
 
+    /// Set an explicit discriminant, emitting e.g. `Red = 0xFF,`.
+    ///
+    /// Only variants without fields can have a discriminant. Combine with
+    /// [`Enum::repr`](crate::Enum::repr) for C-compatible enums.
+    pub fn discriminant(&mut self, value: impl ToString) -> &mut Self {
+        self.discriminant = Some(value.to_string());
+        self
+    }
+
     /// Add an anotation to the variant.
     pub fn annotation(&mut self, annotation: impl Into<String>) -> &mut Self {
         self.annotations.push(annotation.into());
         self
     }
 
+    /// Returns `true` if the variant has the given annotation.
+    pub(crate) fn has_annotation(&self, annotation: &str) -> bool {
+        self.annotations.iter().any(|a| a == annotation)
+    }
+
+    /// Returns the name set by a `#[serde(rename = "...")]` annotation, if
+    /// any.
+    pub(crate) fn serde_rename(&self) -> Option<&str> {
+        self.annotations.iter().find_map(|a| {
+            a.strip_prefix("#[serde(rename = \"")
+                .and_then(|rest| rest.strip_suffix("\")]"))
+        })
+    }
+
+    /// Returns the name serde uses for the variant: its `rename`, or its
+    /// name converted to the enum's `rename_all` case.
+    ///
+    /// Unknown cases leave the name unchanged.
+    pub(crate) fn serde_name(&self, rename_all: Option<&str>) -> String {
+        if let Some(rename) = self.serde_rename() {
+            return rename.to_string();
+        }
+
+        let snake_case = || {
+            let mut snake = String::new();
+            for (i, ch) in self.name.char_indices() {
+                if i > 0 && ch.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            snake
+        };
+
+        match rename_all {
+            Some("lowercase") => self.name.to_ascii_lowercase(),
+            Some("UPPERCASE") => self.name.to_ascii_uppercase(),
+            Some("camelCase") => {
+                let mut chars = self.name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Some("snake_case") => snake_case(),
+            Some("SCREAMING_SNAKE_CASE") => snake_case().to_ascii_uppercase(),
+            Some("kebab-case") => snake_case().replace('_', "-"),
+            Some("SCREAMING-KEBAB-CASE") => snake_case().to_ascii_uppercase().replace('_', "-"),
+            _ => self.name.clone(),
+        }
+    }
+
     /// Formats the variant using the given formatter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variant has both fields and a discriminant.
     pub fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        assert!(
+            self.discriminant.is_none() || self.fields.is_empty(),
+            "variant `{}` with fields cannot have a discriminant",
+            self.name
+        );
+
         for a in &self.annotations {
             write!(fmt, "{}", a)?;
-            write!(fmt, "\n")?;
+            writeln!(fmt)?;
         }
         write!(fmt, "{}", self.name)?;
-        self.fields.fmt(fmt)?;
-        write!(fmt, ",\n")?;
+        self.fields.fmt_unterminated(fmt)?;
+        if let Some(ref discriminant) = self.discriminant {
+            write!(fmt, " = {}", discriminant)?;
+        }
+        writeln!(fmt, ",")?;
 
         Ok(())
     }
 }
+
+#[test]
+fn serde_name_follows_rename_all() {
+    let variant = Variant::new("FastMode");
+    let cases = [
+        ("lowercase", "fastmode"),
+        ("UPPERCASE", "FASTMODE"),
+        ("PascalCase", "FastMode"),
+        ("camelCase", "fastMode"),
+        ("snake_case", "fast_mode"),
+        ("SCREAMING_SNAKE_CASE", "FAST_MODE"),
+        ("kebab-case", "fast-mode"),
+        ("SCREAMING-KEBAB-CASE", "FAST-MODE"),
+    ];
+
+    for (case, expected) in cases {
+        assert_eq!(variant.serde_name(Some(case)), expected);
+    }
+    assert_eq!(variant.serde_name(None), "FastMode");
+}
@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Defines the visibility of an item.
+///
+/// Every `vis` setter accepts a `Visibility` as well as its string form,
+/// which is checked when converted. Use [`str::parse`] to handle an invalid
+/// modifier instead of panicking, or [`Visibility::Raw`] to emit one
+/// verbatim:
+///
+/// ```
+/// use codegen::{Struct, Visibility};
+///
+/// let mut foo = Struct::new("Foo");
+///
+/// // pub(in crate::bar) struct Foo;
+/// foo.vis(Visibility::PubIn("crate::bar".to_string()));
+///
+/// // pub(crate) struct Foo;
+/// foo.vis("pub(crate)");
+///
+/// // crate struct Foo;
+/// foo.vis(Visibility::Raw("crate".to_string()));
+///
+/// assert!("publ".parse::<Visibility>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    /// No visibility modifier, i.e. private to the containing module.
+    Private,
+    /// `pub`
+    Pub,
+    /// `pub(crate)`
+    PubCrate,
+    /// `pub(super)`
+    PubSuper,
+    /// `pub(in <path>)`
+    PubIn(String),
+    /// A modifier emitted verbatim, e.g. `crate`.
+    Raw(String),
+}
+
+impl Visibility {
+    /// Returns the modifier as stored by the items, `None` for private.
+    pub(crate) fn into_modifier(self) -> Option<String> {
+        match self {
+            Visibility::Private => None,
+            vis => Some(vis.to_string()),
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Visibility::Private => Ok(()),
+            Visibility::Pub => write!(fmt, "pub"),
+            Visibility::PubCrate => write!(fmt, "pub(crate)"),
+            Visibility::PubSuper => write!(fmt, "pub(super)"),
+            Visibility::PubIn(ref path) => write!(fmt, "pub(in {})", path),
+            Visibility::Raw(ref vis) => write!(fmt, "{}", vis),
+        }
+    }
+}
+
+/// The error returned when parsing a string that is not a visibility
+/// modifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibilityParseError {
+    input: String,
+}
+
+impl VisibilityParseError {
+    /// Returns the string that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl Display for VisibilityParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "invalid visibility `{}`", self.input)
+    }
+}
+
+impl Error for VisibilityParseError {}
+
+impl FromStr for Visibility {
+    type Err = VisibilityParseError;
+
+    /// Parses a visibility modifier, e.g. `pub(crate)`.
+    ///
+    /// An empty string and `pub(self)` are private.
+    fn from_str(vis: &str) -> Result<Self, Self::Err> {
+        let compact = vis.split_whitespace().collect::<String>();
+        match compact.as_str() {
+            "" | "pub(self)" => Ok(Visibility::Private),
+            "pub" => Ok(Visibility::Pub),
+            "pub(crate)" => Ok(Visibility::PubCrate),
+            "pub(super)" => Ok(Visibility::PubSuper),
+            _ => vis
+                .trim()
+                .strip_prefix("pub")
+                .and_then(|rest| rest.trim_start().strip_prefix('('))
+                .and_then(|rest| rest.trim_start().strip_prefix("in "))
+                .and_then(|rest| rest.trim_end().strip_suffix(')'))
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(|path| Visibility::PubIn(path.to_string()))
+                .ok_or_else(|| VisibilityParseError {
+                    input: vis.to_string(),
+                }),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Visibility {
+    /// Parses a visibility modifier, e.g. `pub(crate)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vis` is not a visibility modifier.
+    fn from(vis: &'a str) -> Self {
+        vis.parse().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl From<String> for Visibility {
+    fn from(vis: String) -> Self {
+        Visibility::from(vis.as_str())
+    }
+}
+
+impl<'a> From<&'a String> for Visibility {
+    fn from(vis: &'a String) -> Self {
+        Visibility::from(vis.as_str())
+    }
+}
+
+#[test]
+fn parse_visibility() {
+    assert_eq!(Visibility::from(""), Visibility::Private);
+    assert_eq!(Visibility::from("pub"), Visibility::Pub);
+    assert_eq!(Visibility::from("pub( crate )"), Visibility::PubCrate);
+    assert_eq!(Visibility::from("pub(super)"), Visibility::PubSuper);
+    assert_eq!(
+        Visibility::from("pub(in crate::foo)"),
+        Visibility::PubIn("crate::foo".to_string())
+    );
+    assert_eq!(Visibility::PubIn("crate::foo".to_string()).to_string(), "pub(in crate::foo)");
+}
+
+#[test]
+fn parse_invalid_visibility() {
+    let err = "publ".parse::<Visibility>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid visibility `publ`");
+}
+
+#[test]
+#[should_panic(expected = "invalid visibility `publ`")]
+fn convert_invalid_visibility() {
+    let _ = Visibility::from("publ");
+}
@@ -62,7 +62,7 @@ fn single_struct_documented_field() {
     let mut struct_ = Struct::new("Foo");
 
     let mut field1 = Field::new("one", "usize");
-    field1.doc(doc.clone());
+    field1.doc(doc);
     struct_.push_field(field1);
 
     let mut field2 = Field::new("two", "usize");
@@ -439,18 +439,19 @@ fn scoped_imports() {
         .import("bar::quux", "quuux::Quuuux", None)
         .new_struct("Foo")
         .field("bar", "Bar")
-        .field("baz", "baz::Baz")
-        .field("quuuux", "quuux::Quuuux");
+        .field("baz", "Baz")
+        .field("quuuux", "Quuuux");
 
     let expect = r#"
 mod foo {
-    use bar::{Bar, baz};
-    use bar::quux::quuux;
+    use bar::Bar;
+    use bar::baz::Baz;
+    use bar::quux::quuux::Quuuux;
 
     struct Foo {
         bar: Bar,
-        baz: baz::Baz,
-        quuuux: quuux::Quuuux,
+        baz: Baz,
+        quuuux: Quuuux,
     }
 }"#;
 
@@ -690,3 +691,2766 @@ mod foo {
 
     assert_eq!(scope.to_string(), &expect[1..]);
 }
+
+#[test]
+fn struct_where_clause_sorted() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .generic("T, U, V")
+        .bound("V", "Baz")
+        .bound("T", "Foo")
+        .bound("U", "Bar")
+        .sort_bounds(true)
+        .field("one", "T")
+        .field("two", "U")
+        .field("three", "V");
+
+    let expect = r#"
+struct Foo<T, U, V>
+where T: Foo,
+      U: Bar,
+      V: Baz,
+{
+    one: T,
+    two: U,
+    three: V,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_with_builder_setter() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("FooBuilder")
+        .push_fn(Function::builder_setter("name", "String"));
+
+    let expect = r#"
+impl FooBuilder {
+    #[must_use]
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_reference_and_pointer_args() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("write")
+        .arg("buf", "&mut [u8]")
+        .arg("ptr", "*const u8");
+
+    scope.new_struct("Foo").generic("'a").field("data", "&'a [u8]");
+
+    let expect = r#"
+struct Foo<'a> {
+    data: &'a [u8],
+}
+
+fn write(buf: &mut [u8], ptr: *const u8) {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn test_fn_in_mod() {
+    let mut scope = Scope::new();
+
+    let module = scope.new_module("tests");
+    module.attr("cfg(test)");
+    module.new_test_fn("it_works").line("assert_eq!(2 + 2, 4);");
+    module
+        .new_test_fn("it_panics")
+        .attr("should_panic")
+        .line("panic!();");
+
+    let expect = r#"
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[should_panic]
+    fn it_panics() {
+        panic!();
+    }
+
+    #[test]
+    fn it_works() {
+        assert_eq!(2 + 2, 4);
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_with_ordered_attributes() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .attr("non_exhaustive")
+        .repr("C")
+        .cfg_attr("feature = \"serde\", derive(Serialize)")
+        .derive("Debug")
+        .cfg("feature = \"foo\"")
+        .doc("Hello some docs")
+        .field("one", "u8");
+
+    let expect = r#"
+/// Hello some docs
+#[cfg(feature = "foo")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[repr(C)]
+#[non_exhaustive]
+struct Foo {
+    one: u8,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn module_with_doc() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_module("foo")
+        .doc("Hello, this is a module doc\nthat continues on another line.")
+        .vis("pub")
+        .new_struct("Foo");
+
+    let expect = r#"
+/// Hello, this is a module doc
+/// that continues on another line.
+pub mod foo {
+    struct Foo;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn drop_impl() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Foo").field("handle", "RawHandle");
+    scope
+        .new_drop_impl("Foo")
+        .line("unsafe { close(self.handle) };");
+
+    let expect = r#"
+struct Foo {
+    handle: RawHandle,
+}
+
+impl Drop for Foo {
+    fn drop(&mut self) {
+        unsafe { close(self.handle) };
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn item_ordering() {
+    let mut scope = Scope::new();
+
+    scope.new_trait("Foo");
+    scope.new_impl("Foo").new_fn("new");
+    scope.new_module("Foo");
+    scope.new_fn("Foo");
+    scope.new_enum("Bar").new_variant("Baz");
+    scope.new_struct("Foo");
+    scope.new_type_alias("Foo", "u8");
+
+    let expect = r#"
+enum Bar {
+    Baz,
+}
+
+type Foo = u8;
+
+struct Foo;
+
+fn Foo() {
+}
+
+impl Foo {
+    fn new() {
+    }
+}
+
+mod Foo {
+}
+
+trait Foo {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn item_ordering_tricky_names() {
+    let mut scope = Scope::new();
+
+    scope.new_impl("r#type");
+    scope.new_impl("r");
+    scope.new_struct("r#type");
+    scope.new_struct("r");
+    scope.new_struct("Fooastruct");
+    scope.new_impl("Foo");
+    scope.new_struct("Foo");
+
+    let expect = r#"
+struct Foo;
+
+impl Foo {
+}
+
+struct Fooastruct;
+
+struct r;
+
+impl r {
+}
+
+struct r#type;
+
+impl r#type {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_literal() {
+    let mut foo = Struct::new("Foo");
+    foo.field("x", "u8").field("y", "u8");
+
+    assert_eq!(foo.to_literal([("x", "1"), ("y", "2")]), "Foo { x: 1, y: 2 }");
+    assert_eq!(foo.to_literal_with_rest([("x", "1")], "base"), "Foo { x: 1, ..base }");
+    assert_eq!(foo.to_literal_with_rest(Vec::<(&str, &str)>::new(), "base"), "Foo { ..base }");
+}
+
+#[test]
+fn sorted_imports_unsorted_items() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion).sort_imports(true);
+
+    scope.push_import("std::fmt", "Write", None);
+    scope.push_import("std::collections", "HashMap", None);
+    scope.push_import("std::collections", "BTreeMap", None);
+
+    scope.new_struct("Foo");
+    scope.new_impl("Foo");
+    scope.new_struct("Bar");
+
+    let expect = r#"
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+struct Foo;
+
+impl Foo {
+}
+
+struct Bar;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_const_generic() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("f")
+        .generic("T")
+        .const_generic("N", "usize")
+        .ret("[T; N]")
+        .line("todo!()");
+
+    let expect = r#"
+fn f<T, const N: usize>() -> [T; N] {
+    todo!()
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_items() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Foo");
+    scope.new_impl("Foo");
+    scope.new_fn("bar");
+    scope.raw("// raw");
+
+    let kinds = scope
+        .items()
+        .iter()
+        .map(|item| match item {
+            Item::Struct(v) => format!("struct {}", v.ty().name()),
+            Item::Impl(v) => format!("impl {}", v.target().name()),
+            Item::Function(v) => format!("fn {}", v.name()),
+            Item::Raw(v) => v.clone(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(kinds, ["struct Foo", "impl Foo", "fn bar", "// raw"]);
+}
+
+#[test]
+fn enum_discriminant_fn() {
+    let mut scope = Scope::new();
+
+    let mut kind = Enum::new("IpAddrKind");
+    kind.derive("Clone").derive("Copy").repr("u8");
+    kind.new_variant("V4");
+    kind.new_variant("V6");
+
+    scope.push_impl(kind.generate_discriminant_fn("u8"));
+    scope.push_enum(kind);
+
+    let expect = r#"
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum IpAddrKind {
+    V4,
+    V6,
+}
+
+impl IpAddrKind {
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "fieldless")]
+fn enum_discriminant_fn_with_fields() {
+    let mut kind = Enum::new("IpAddr");
+    kind.new_variant("V4").tuple("u32");
+    kind.generate_discriminant_fn("u8");
+}
+
+#[test]
+fn struct_with_repr_transparent() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .repr("transparent")
+        .generic("T")
+        .field("inner", "u32")
+        .field("_marker", "std::marker::PhantomData<T>");
+
+    let expect = r#"
+#[repr(transparent)]
+struct Foo<T> {
+    inner: u32,
+    _marker: std::marker::PhantomData<T>,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "repr(transparent)")]
+fn struct_with_repr_transparent_multiple_fields() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .repr("transparent")
+        .field("one", "u32")
+        .field("two", "u32");
+
+    scope.to_string();
+}
+
+#[test]
+fn scope_into_module() {
+    let mut inner = Scope::new();
+    inner.push_import("bar", "Bar", None);
+    inner.new_struct("Foo").field("bar", "Bar");
+
+    let mut scope = Scope::new();
+    scope.push_module(inner.into_module("foo"));
+
+    let expect = r#"
+mod foo {
+    use bar::Bar;
+
+    struct Foo {
+        bar: Bar,
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_with_multiline_str_const() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Foo")
+        .associate_const("ESCAPED", "&str", "\"first\nsecond\"", "pub");
+
+    scope
+        .new_impl("Bar")
+        .multiline_str(MultilineStr::Concat)
+        .associate_const("CONCAT", "&str", "\"first\nsecond\"", "pub");
+
+    let expect = r#"
+impl Bar {
+    pub const CONCAT: &str = concat!("first\n", "second");
+}
+
+impl Foo {
+    pub const ESCAPED: &str = "first\nsecond";
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_with_associated_type_bound() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Wrapper")
+        .generic("T")
+        .target_generic("T")
+        .impl_trait("Trait")
+        .bound_ty(Type::new("T::Item"), "Clone");
+
+    let expect = r#"
+impl<T> Trait for Wrapper<T>
+where T::Item: Clone,
+{
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_generate_default() {
+    let mut scope = Scope::new();
+
+    let mut foo = Struct::new("Foo");
+    foo.new_field("retries", "u32").default_expr("3");
+    foo.field("name", "String");
+
+    scope.push_impl(foo.generate_default());
+    scope.push_struct(foo);
+
+    let expect = r#"
+struct Foo {
+    retries: u32,
+    name: String,
+}
+
+impl Default for Foo {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            name: Default::default(),
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_generate_default_generic() {
+    let mut foo = Struct::new("Wrapper");
+    foo.lifetime("a")
+        .generic("T")
+        .bound("T", "Clone")
+        .field("name", "&'a str")
+        .field("value", "T");
+
+    let mut scope = Scope::new();
+    scope.push_impl(foo.generate_default());
+
+    let expect = r#"
+impl<'a, T> Default for Wrapper<'a, T>
+where T: Default + Clone,
+{
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            value: Default::default(),
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_with_test_only_derive() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .derive("Debug")
+        .cfg_attr_with("test", "derive(PartialEq)")
+        .field("one", "u8");
+
+    let expect = r#"
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+struct Foo {
+    one: u8,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_doc_example() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("add")
+        .vis("pub")
+        .doc("Adds two numbers.")
+        .doc_example("use foo::add;\n\nassert_eq!(add(1, 2), 3);")
+        .arg("a", "u32")
+        .arg("b", "u32")
+        .ret("u32")
+        .line("a + b");
+
+    let expect = r#"
+/// Adds two numbers.
+///
+/// ```rust
+/// use foo::add;
+///
+/// assert_eq!(add(1, 2), 3);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_default_visibility() {
+    let mut scope = Scope::new();
+    scope.default_visibility("pub(crate)");
+
+    scope.new_struct("Foo");
+    scope.new_struct("Bar").vis("pub");
+    scope.new_impl("Foo").new_fn("new");
+    scope.new_fn("baz");
+    scope.new_module("inner").new_struct("Inner");
+
+    let expect = r#"
+pub struct Bar;
+
+pub(crate) struct Foo;
+
+impl Foo {
+    fn new() {
+    }
+}
+
+pub(crate) fn baz() {
+}
+
+pub(crate) mod inner {
+    struct Inner;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn append_to_empty_scope_is_idempotent() {
+    let mut scopes = vec![];
+
+    scopes.push(Scope::new());
+
+    let mut scope = Scope::new();
+    scope.raw("// first raw");
+    scope.push_import("std::fmt", "Debug", None);
+    scope.push_import("std::fmt", "Display", Some("Show"));
+    scope.new_import("crate::foo", "Foo", None).vis("pub");
+    scope.new_struct("Foo").doc("Foo docs\nsecond line").field("one", "u8");
+    scope.raw("// second raw");
+    scope.new_impl("Foo").new_fn("new").ret("Self").line("Foo { one: 0 }");
+    scope.new_fn("bar");
+    scopes.push(scope);
+
+    let mut scope = Scope::new();
+    scope.new_module("inner").import("bar", "Bar", None).new_struct("Baz");
+    scope.new_enum("Kind").new_variant("A");
+    scope.new_trait("Trait").new_fn("run");
+    scope.new_type_alias("Alias", "u8");
+    scopes.push(scope);
+
+    for scope in scopes {
+        assert_eq!(Scope::new().append(&scope).to_string(), scope.to_string());
+    }
+}
+
+#[test]
+fn struct_with_serde_attributes() {
+    let mut scope = Scope::new();
+
+    let foo = scope
+        .new_struct("Foo")
+        .derive("Serialize")
+        .serde_rename_all("camelCase");
+    foo.new_field("first_name", "Option<String>")
+        .serde_skip_if("Option::is_none");
+
+    let expect = r#"
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Foo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_name: Option<String>,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_impl_trait_args() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("f")
+        .arg("x", "impl Into<String>")
+        .arg("y", Type::impl_trait(["AsRef<[u8]>"]));
+
+    let expect = r#"
+fn f(x: impl Into<String>, y: impl AsRef<[u8]>) {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn ffi_export_fn() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("ffi_add")
+        .no_mangle()
+        .inline(InlineKind::Never)
+        .vis("pub")
+        .extern_abi("C")
+        .arg("a", "u32")
+        .arg("b", "u32")
+        .ret("u32")
+        .line("a + b");
+
+    let expect = r#"
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn ffi_add(a: u32, b: u32) -> u32 {
+    a + b
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn hoist_imports() {
+    let mut scope = Scope::new();
+    scope.push_import("std::collections", "HashMap", None);
+    scope.push_import("std::fmt", "Debug", None);
+    scope.new_struct("Foo").field("inner", "Box<dyn Debug>");
+
+    let inner = scope.new_module("inner");
+    inner.new_struct("Bar").field("map", "HashMap<u8, u8>");
+    inner.new_module("nested").new_fn("debug").ret("impl Debug").line("()");
+
+    scope.hoist_imports().unwrap();
+
+    let expect = r#"
+use std::fmt::Debug;
+
+struct Foo {
+    inner: Box<dyn Debug>,
+}
+
+mod inner {
+    use std::collections::HashMap;
+
+    struct Bar {
+        map: HashMap<u8, u8>,
+    }
+
+    mod nested {
+        use std::fmt::Debug;
+
+        fn debug() -> impl Debug {
+            ()
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn hoist_imports_into_several_modules() {
+    let mut scope = Scope::new();
+    scope.push_import("std::sync", "Arc", None);
+    scope.new_import("crate::model", "User", None).vis("pub");
+    scope.new_module("a").new_struct("A").field("inner", "Arc<crate::model::User>");
+    scope.new_module("b").new_struct("B").field("inner", "Arc<u8>");
+
+    scope.hoist_imports().unwrap();
+
+    let expect = r#"
+pub use crate::model::User;
+
+mod a {
+    use std::sync::Arc;
+
+    struct A {
+        inner: Arc<crate::model::User>,
+    }
+}
+
+mod b {
+    use std::sync::Arc;
+
+    struct B {
+        inner: Arc<u8>,
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_with_cfg_builder() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .cfg(Cfg::all([
+            Cfg::feature("a"),
+            Cfg::not(Cfg::any([Cfg::name("windows"), Cfg::key_value("target_os", "wasi")])),
+        ]))
+        .cfg_attr_with(Cfg::name("test"), "derive(PartialEq)")
+        .field("one", "usize");
+
+    let expect = r#"
+#[cfg(all(feature = "a", not(any(windows, target_os = "wasi"))))]
+#[cfg_attr(test, derive(PartialEq))]
+struct Foo {
+    one: usize,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn enum_with_default_variant() {
+    let mut scope = Scope::new();
+
+    let e = scope.new_enum("Mode").derive("Debug");
+    e.new_variant("Fast");
+    e.new_variant("Slow");
+    e.new_variant("Custom").tuple("u32");
+    e.default_variant("Slow");
+
+    let expect = r#"
+#[derive(Debug, Default)]
+enum Mode {
+    Fast,
+    #[default]
+    Slow,
+    Custom(u32),
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "the default variant must be fieldless")]
+fn enum_with_default_variant_with_fields() {
+    let mut e = Enum::new("Mode");
+    e.new_variant("Custom").tuple("u32");
+    e.default_variant("Custom");
+}
+
+#[test]
+fn deeply_nested_blocks() {
+    let mut scope = Scope::new();
+
+    let mut arm = Block::new("Some(x) =>");
+    arm.line("x");
+
+    let mut match_block = Block::new("match value");
+    match_block.push_block(arm).line("None => 0,");
+
+    let mut if_block = Block::new("if flag");
+    if_block.push_block(match_block);
+
+    scope
+        .new_module("outer")
+        .new_impl("Foo")
+        .new_fn("get")
+        .arg("flag", "bool")
+        .arg("value", "Option<u8>")
+        .ret("u8")
+        .push_block(if_block)
+        .line("0");
+
+    let expect = r#"
+mod outer {
+    impl Foo {
+        fn get(flag: bool, value: Option<u8>) -> u8 {
+            if flag {
+                match value {
+                    Some(x) => {
+                        x
+                    }
+                    None => 0,
+                }
+            }
+            0
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+
+    use std::fmt::Write;
+
+    let mut ret = String::new();
+    let mut fmt = Formatter::new(&mut ret);
+    fn nest(depth: usize, fmt: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(fmt, "level{}", depth)?;
+        if depth < 5 {
+            fmt.block(|fmt| nest(depth + 1, fmt))?;
+        }
+        Ok(())
+    }
+    nest(0, &mut fmt).unwrap();
+
+    let levels = ret.lines().filter(|line| line.contains("level")).collect::<Vec<_>>();
+    assert_eq!(levels.len(), 6);
+    for (depth, line) in levels.into_iter().enumerate() {
+        assert_eq!(line, format!("{}level{}", " ".repeat(4 * depth), depth));
+    }
+}
+
+#[test]
+fn enum_generate_clone() {
+    let mut scope = Scope::new();
+
+    let mut e = Enum::new("Message");
+    e.generic("T").generic("U");
+    e.new_variant("Data").tuple("Vec<T>").tuple("PhantomData<U>");
+    e.new_variant("Point").named("x", "T").named("y", "T");
+    e.new_variant("Empty");
+
+    scope.push_impl(e.generate_clone());
+
+    let expect = r#"
+impl<T, U> Clone for Message<T, U>
+where T: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Data(f0, f1) => Self::Data(f0.clone(), f1.clone()),
+            Self::Point { x, y } => Self::Point { x: x.clone(), y: y.clone() },
+            Self::Empty => Self::Empty,
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_mark_generated() {
+    let mut scope = Scope::new();
+    scope.raw("#![allow(dead_code)]");
+    scope.new_struct("Foo");
+    scope.mark_generated(Some("build.rs"));
+
+    let expect = r#"
+// @generated by build.rs
+#![allow(dead_code)]
+
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+
+    scope.mark_generated(None);
+    assert_eq!(scope.to_string().lines().next(), Some("// @generated"));
+}
+
+#[test]
+fn struct_bounds_by_fields() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Named")
+        .generic("T")
+        .bound("T", "Clone")
+        .field("one", "T");
+    scope
+        .new_struct("Tuple")
+        .generic("T")
+        .bound("T", "Clone")
+        .bound("T", "Debug")
+        .tuple_field(None, "T");
+    scope
+        .new_struct("Unit")
+        .generic("T")
+        .bound("T", "Clone");
+
+    let expect = r#"
+struct Named<T>
+where T: Clone,
+{
+    one: T,
+}
+
+struct Tuple<T>(T)
+where T: Clone + Debug;
+
+struct Unit<T>
+where T: Clone;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn trait_fn_with_projection_ret() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Meters")
+        .impl_trait("Add")
+        .associate_type("Output", "Meters")
+        .new_fn("add")
+        .arg_self()
+        .arg("rhs", "Self")
+        .ret(Type::projection("Self", "Output"))
+        .line("Meters(self.0 + rhs.0)");
+
+    let expect = r#"
+impl Add for Meters {
+    type Output = Meters;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Meters(self.0 + rhs.0)
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_const_and_type_spacing() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Foo")
+        .impl_trait("Bar")
+        .associate_const("SIZE", "usize", "4", "pub")
+        .associate_type("Item", "u32");
+
+    let expect = r#"
+impl Bar for Foo {
+    pub const SIZE: usize = 4;
+
+    type Item = u32;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_aggregate_modules() {
+    let mut scope = Scope::new();
+    scope.aggregate_modules([("parser", Visibility::Private, true), ("lexer", Visibility::PubCrate, false)]);
+
+    let expect = r#"
+pub use parser::*;
+
+pub(crate) mod lexer;
+
+mod parser;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+    assert!(scope.get_module("lexer").is_some());
+}
+
+#[test]
+fn fn_returning_references_to_self() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Wrapper").generic("T").target_generic("T");
+    imp.new_fn("get")
+        .arg_ref_self()
+        .ret(Type::reference("T", None))
+        .line("&self.0");
+    imp.new_fn("get_with")
+        .generic("'a")
+        .arg_ref_self_with_lifetime("'a")
+        .ret("&'a Vec<T>")
+        .line("&self.1");
+
+    let expect = r#"
+impl<T> Wrapper<T> {
+    fn get(&self) -> &T {
+        &self.0
+    }
+
+    fn get_with<'a>(&'a self) -> &'a Vec<T> {
+        &self.1
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_clear() {
+    let mut scope = Scope::new();
+    scope.push_import("std::fmt", "Debug", None);
+    scope.new_struct("Foo").derive("Debug");
+
+    scope.clear(false);
+    assert_eq!(scope.to_string(), "");
+
+    scope.new_struct("Bar");
+    assert_eq!(scope.to_string(), "struct Bar;");
+
+    let mut module = Module::new("foo");
+    module.doc("Docs").new_struct("Foo");
+    module.clear(true).new_struct("Bar");
+
+    let mut scope = Scope::new();
+    scope.push_module(module);
+
+    let expect = r#"
+/// Docs
+mod foo {
+    struct Bar;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn grouped_reexports_with_alias() {
+    let mut scope = Scope::new();
+    scope.new_import("a", "B", None).vis("pub");
+    scope.new_import("a", "C", Some("D")).vis("pub");
+    scope.new_import("a", "E", Some("F"));
+
+    let expect = r#"
+pub use a::{B, C as D};
+use a::E as F;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "glob imports cannot be renamed")]
+fn renamed_glob_import() {
+    Scope::new().push_import("a", "*", Some("B"));
+}
+
+#[test]
+fn duplicate_generics() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .generic("T")
+        .generic("T")
+        .field("one", "T");
+    scope
+        .new_impl("Foo")
+        .generic("T")
+        .generic("T")
+        .target_generic("T");
+
+    let expect = r#"
+struct Foo<T> {
+    one: T,
+}
+
+impl<T> Foo<T> {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_with_doc_expr() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .doc("A foo.")
+        .doc_expr(r#"concat!("Since ", env!("CARGO_PKG_VERSION"))"#)
+        .derive("Debug");
+
+    let expect = r#"
+/// A foo.
+#[doc = concat!("Since ", env!("CARGO_PKG_VERSION"))]
+#[derive(Debug)]
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_cfg_group() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Always");
+    scope.cfg_group("gated", Cfg::feature("extra"), |scope| {
+        scope.new_struct("Foo");
+        scope.new_fn("bar");
+    });
+
+    let expect = r#"
+struct Always;
+
+#[cfg(feature = "extra")]
+mod gated {
+    struct Foo;
+
+    fn bar() {
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn lifetimes_before_generics() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Ref")
+        .lifetime("'a")
+        .field("inner", "&'a str");
+    scope
+        .new_enum("Either")
+        .generic("T")
+        .lifetime("a")
+        .new_variant("Left")
+        .tuple("&'a T");
+
+    let imp = scope
+        .new_impl("Foo")
+        .generic("T")
+        .lifetime("'a")
+        .target_generic("'a")
+        .target_generic("T");
+    imp.new_fn("get")
+        .generic("U")
+        .lifetime("'b")
+        .arg("x", "&'b U")
+        .ret("&'b U")
+        .line("x");
+
+    let expect = r#"
+enum Either<'a, T> {
+    Left(&'a T),
+}
+
+impl<'a, T> Foo<'a, T> {
+    fn get<'b, U>(x: &'b U) -> &'b U {
+        x
+    }
+}
+
+struct Ref<'a> {
+    inner: &'a str,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_array_const() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Foo")
+        .associate_const("K", "[u8; 4]", "[0; 4]", "pub")
+        .associate_const("V", "[Vec<u8>; 2]", "[Vec::new(), Vec::new()]", "pub");
+
+    let expect = r#"
+impl Foo {
+    pub const K: [u8; 4] = [0; 4];
+    pub const V: [Vec<u8>; 2] = [Vec::new(), Vec::new()];
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_insertion_ordering() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope.raw("// header");
+    scope.push_import("std::fmt", "Debug", None);
+    scope.new_impl("Foo").impl_trait("Debug");
+    scope.new_struct("Foo");
+    scope.new_fn("alpha");
+
+    let expect = r#"
+// header
+
+use std::fmt::Debug;
+
+impl Debug for Foo {
+}
+
+struct Foo;
+
+fn alpha() {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+
+    scope.set_ordering(ItemOrdering::Alphabetical);
+    assert!(scope.to_string().contains("struct Foo;\n\nimpl Debug for Foo {\n}"));
+}
+
+#[test]
+fn conversion_impls_sort_near_type() {
+    let mut scope = Scope::new();
+
+    scope.new_impl("Vec<u8>").impl_trait("TryFrom<Foo>");
+    scope.new_impl("Foo").impl_trait("TryFrom<Bar>");
+    scope.new_struct("Foo");
+    scope.new_struct("Bar");
+    scope.new_struct("Zed");
+
+    let expect = r#"
+struct Bar;
+
+struct Foo;
+
+impl TryFrom<Foo> for Vec<u8> {
+}
+
+impl TryFrom<Bar> for Foo {
+}
+
+struct Zed;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn custom_conversion_traits() {
+    let mut scope = Scope::new();
+    scope.set_conversion_traits(["From", "Extend"]);
+
+    scope.new_impl("Vec<u8>").impl_trait("TryFrom<Foo>");
+    scope.new_impl("Vec<Foo>").impl_trait("Extend<Foo>");
+    scope.new_struct("Foo");
+    scope.new_struct("Zed");
+
+    let expect = r#"
+struct Foo;
+
+impl Extend<Foo> for Vec<Foo> {
+}
+
+impl TryFrom<Foo> for Vec<u8> {
+}
+
+struct Zed;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_fmt_to_writer() {
+    let mut scope = Scope::new();
+    scope.push_import("std::fmt", "Debug", None);
+    scope
+        .new_impl("Foo")
+        .new_fn("bar")
+        .arg_ref_self()
+        .line("todo!()");
+    scope.new_struct("Foo").derive("Debug").field("one", "usize");
+
+    let mut buf = Vec::new();
+    scope.fmt_to_writer(&mut buf).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), scope.to_string() + "\n");
+
+    let path = std::env::temp_dir().join(format!("codegen-write-to-file-{}.rs", std::process::id()));
+    scope.write_to_file(&path).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written, scope.to_string() + "\n");
+}
+
+#[test]
+fn struct_with_dedented_docs() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Foo").doc(dedent(
+        "
+        A foo.
+
+        ```
+        let foo = Foo;
+        ```",
+    ));
+
+    let expect = r#"
+/// A foo.
+///
+/// ```
+/// let foo = Foo;
+/// ```
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_qualifiers() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope.new_fn("a").vis("pub").set_const(true).ret("u8").line("0");
+    scope.new_fn("b").vis("pub").set_async(true).set_unsafe(true);
+    scope.new_fn("c").set_const(true).set_unsafe(true).generic("T");
+    scope.new_fn("d").set_unsafe(true).extern_abi("C");
+
+    let expect = r#"
+pub const fn a() -> u8 {
+    0
+}
+
+pub async unsafe fn b() {
+}
+
+const unsafe fn c<T>() {
+}
+
+unsafe extern "C" fn d() {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "functions cannot be both `const` and `async`")]
+fn fn_const_async() {
+    let mut scope = Scope::new();
+    scope.new_fn("f").set_const(true).set_async(true);
+    scope.to_string();
+}
+
+#[test]
+fn scope_no_std_no_main() {
+    let mut scope = Scope::new();
+    scope.mark_generated(None);
+    scope.push_import("core::panic", "PanicInfo", None);
+    scope.no_std().no_main().no_std();
+    scope
+        .new_fn("panic")
+        .attr("panic_handler")
+        .arg("_info", "&PanicInfo")
+        .ret("!")
+        .line("loop {}");
+
+    let expect = r#"
+// @generated
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn extern_fns_and_block() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope
+        .new_fn("add")
+        .vis("pub")
+        .extern_abi("C")
+        .arg("a", "i32")
+        .arg("b", "i32")
+        .ret("i32")
+        .line("a + b");
+    scope.new_fn("callback").set_unsafe(true).extern_abi("system");
+
+    let block = scope.new_extern_block("C");
+    block.new_fn("abs").arg("input", "i32").ret("i32");
+    block.new_fn("puts").vis("pub").arg("s", "*const c_char").ret("i32");
+
+    let expect = r#"
+pub extern "C" fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+unsafe extern "system" fn callback() {
+}
+
+extern "C" {
+    fn abs(input: i32) -> i32;
+    pub fn puts(s: *const c_char) -> i32;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "foreign fns cannot have a body")]
+fn extern_block_fn_with_body() {
+    let mut scope = Scope::new();
+    scope.new_extern_block("C").new_fn("abs").line("0");
+    scope.to_string();
+}
+
+#[test]
+fn extern_block_with_cfg() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_extern_block("C")
+        .cfg("target_os = \"linux\"")
+        .new_fn("getpid")
+        .ret("i32");
+
+    let expect = r#"
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn getpid() -> i32;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_and_fn_with_allow() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Foo").allow("clippy::new_without_default");
+    imp.new_fn("new")
+        .allow("clippy::too_many_arguments")
+        .attr("inline")
+        .ret("Self")
+        .line("Foo");
+
+    let expect = r#"
+#[allow(clippy::new_without_default)]
+impl Foo {
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    fn new() -> Self {
+        Foo
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn const_and_static_items() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_const("MAX_LEN", "usize", "64")
+        .vis("pub")
+        .doc("Maximum length.");
+    scope.new_const("GREETING", "&'static str", "\"hello\"").set_static(true);
+    scope.new_const("COUNTER", "u32", "0").set_mut(true);
+
+    let expect = r#"
+static mut COUNTER: u32 = 0;
+
+static GREETING: &'static str = "hello";
+
+/// Maximum length.
+pub const MAX_LEN: usize = 64;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn const_items_in_module_are_sorted() {
+    let mut scope = Scope::new();
+
+    let module = scope.new_module("consts");
+    module.new_const("B", "u8", "2");
+    module.new_struct("Alpha");
+    module.push_const(Const::new("A", "u8", "1"));
+
+    let expect = r#"
+mod consts {
+    const A: u8 = 1;
+
+    struct Alpha;
+
+    const B: u8 = 2;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn multiline_const_values() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope.new_const("ESCAPED", "&str", "\"a\nb\"");
+    scope
+        .new_const("JOINED", "&str", "\"a\nb\"")
+        .set_static(true)
+        .multiline_str(MultilineStr::Concat);
+
+    let expect = r#"
+const ESCAPED: &str = "a\nb";
+
+static JOINED: &str = concat!("a\n", "b");"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn multiline_const_line_continuation() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope.new_const("CONTINUED", "&str", "\"abc\\\n   def\"");
+    scope
+        .new_const("MIXED", "&str", "\"a\\\\\nb\\\n c\"")
+        .multiline_str(MultilineStr::Concat);
+
+    let expect = r#"
+const CONTINUED: &str = "abc\
+   def";
+
+const MIXED: &str = concat!("a\\\n", "b\
+ c");"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn qualify_colliding_imports() {
+    let mut scope = Scope::new();
+    scope.qualify_collisions(true);
+    scope.push_import("std::io", "Error", None);
+    scope.push_import("std::fmt", "Error", None);
+    scope.push_import("std::collections", "HashMap", None);
+
+    scope
+        .new_struct("Errors")
+        .field("io", "std::io::Error")
+        .field("fmt", "std::fmt::Error")
+        .field("map", "std::collections::HashMap<String, std::io::Error>");
+
+    let expect = r#"
+use std::collections::HashMap;
+
+struct Errors {
+    io: std::io::Error,
+    fmt: std::fmt::Error,
+    map: HashMap<String, std::io::Error>,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn qualify_collisions_in_bounds_and_receivers() {
+    let mut scope = Scope::new();
+    scope.qualify_collisions(true);
+    scope.push_import("std::io", "Error", None);
+    scope.push_import("std::fmt", "Error", None);
+    scope.push_import("std::pin", "Pin", None);
+    scope.push_import("crate::types", "Size", None);
+
+    let imp = scope.new_impl("Reader");
+    imp.bound_ty("std::io::Error", "Send").bound_ty("std::fmt::Error", "Sync");
+    imp.new_fn("poll")
+        .arg_self_ty("std::pin::Pin<&mut Self>")
+        .const_generic("N", "crate::types::Size");
+
+    let expect = r#"
+use std::pin::Pin;
+use crate::types::Size;
+
+impl Reader
+where std::io::Error: Send,
+      std::fmt::Error: Sync,
+{
+    fn poll<const N: Size>(self: Pin<&mut Self>) {
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+
+    let mut scope = Scope::new();
+    scope.qualify_collisions(true);
+    scope.push_import("std::io", "Error", None);
+    scope.new_impl("Reader").bound_ty("std::io::Error", "Send");
+
+    let expect = r#"
+use std::io::Error;
+
+impl Reader
+where Error: Send,
+{
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn qualify_collisions_shortens_imported_types() {
+    let mut scope = Scope::new();
+    scope.qualify_collisions(true);
+    scope.push_import("std::io", "Error", None);
+
+    scope
+        .new_fn("read")
+        .ret("Result<(), std::io::Error>")
+        .line("Ok(())");
+
+    let expect = r#"
+use std::io::Error;
+
+fn read() -> Result<(), Error> {
+    Ok(())
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn struct_generate_eq_compares_subset() {
+    let mut scope = Scope::new();
+
+    let mut s = Struct::new("User");
+    s.field("id", "u64").field("name", "String");
+
+    for imp in s.generate_eq(&["id"]) {
+        scope.push_impl(imp);
+    }
+    scope.push_struct(s);
+
+    let expect = r#"
+struct User {
+    id: u64,
+    name: String,
+}
+
+impl PartialEq for User {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for User {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "struct `User` has no field `email`")]
+fn struct_generate_eq_unknown_field() {
+    let mut s = Struct::new("User");
+    s.field("id", "u64");
+    s.generate_eq(&["email"]);
+}
+
+#[test]
+fn union_with_repr() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_union("MyUnion")
+        .vis("pub")
+        .derive("Clone")
+        .derive("Copy")
+        .repr("C")
+        .field("a", "u32")
+        .field("b", "f32");
+
+    let expect = r#"
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union MyUnion {
+    a: u32,
+    b: f32,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "union `Empty` must have at least one field")]
+fn union_without_fields() {
+    let mut scope = Scope::new();
+    scope.new_union("Empty");
+    scope.to_string();
+}
+
+#[test]
+fn multiple_bounds_on_same_param() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .generic("T")
+        .bound("T", "Clone")
+        .bound("T", "Debug")
+        .field("t", "T");
+
+    scope
+        .new_impl("Foo")
+        .generic("T")
+        .target_generic("T")
+        .bound("T", "Clone")
+        .bound("T", "Send")
+        .bound("T", "'static");
+
+    let expect = r#"
+struct Foo<T>
+where T: Clone + Debug,
+{
+    t: T,
+}
+
+impl<T> Foo<T>
+where T: Clone + Send + 'static,
+{
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn skip_rustfmt() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Matrix")
+        .derive("Debug")
+        .skip_rustfmt()
+        .field("rows", "[[u8; 3]; 3]");
+
+    scope
+        .new_impl("Matrix")
+        .skip_rustfmt()
+        .new_fn("identity")
+        .skip_rustfmt()
+        .ret("Self")
+        .line("Matrix { rows: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] }");
+
+    let expect = r#"
+#[derive(Debug)]
+#[rustfmt::skip]
+struct Matrix {
+    rows: [[u8; 3]; 3],
+}
+
+#[rustfmt::skip]
+impl Matrix {
+    #[rustfmt::skip]
+    fn identity() -> Self {
+        Matrix { rows: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn typed_visibility() {
+    let mut scope = Scope::new();
+
+    let s = scope.new_struct("Foo").vis(Visibility::PubIn("crate::bar".to_string()));
+    s.new_field("one", "usize").vis(Visibility::PubCrate);
+    s.new_field("two", "usize").vis("pub(super)");
+    s.new_field("three", "usize").vis(Visibility::Private);
+
+    let expect = r#"
+pub(in crate::bar) struct Foo {
+    pub(crate) one: usize,
+    pub(super) two: usize,
+    three: usize,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn raw_visibility() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Foo").vis(Visibility::Raw("crate".to_string()));
+
+    assert_eq!(scope.to_string(), "crate struct Foo;");
+}
+
+#[test]
+#[should_panic(expected = "invalid visibility `publ`")]
+fn invalid_visibility() {
+    Struct::new("Foo").vis("publ");
+}
+
+#[test]
+fn derives_are_deduplicated() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .derive("Debug")
+        .derive("Clone")
+        .derive("Debug")
+        .derive("Clone");
+
+    let expect = r#"
+#[derive(Debug, Clone)]
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn sorted_derives() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_enum("Foo")
+        .sort_derives(true)
+        .derive("PartialEq")
+        .derive("Debug")
+        .derive("Clone")
+        .derive("Debug")
+        .new_variant("Bar");
+
+    let expect = r#"
+#[derive(Clone, Debug, PartialEq)]
+enum Foo {
+    Bar,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_typed_self() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Fut");
+    imp.new_fn("poll")
+        .arg_self_ty("Pin<&mut Self>")
+        .arg("cx", "&mut Context<'_>")
+        .ret("Poll<()>")
+        .line("Poll::Ready(())");
+    imp.new_fn("into_inner")
+        .arg_self_ty("Box<Self>")
+        .ret("Self")
+        .line("*self");
+
+    let expect = r#"
+impl Fut {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Ready(())
+    }
+
+    fn into_inner(self: Box<Self>) -> Self {
+        *self
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn enum_with_discriminants() {
+    let mut scope = Scope::new();
+
+    let e = scope.new_enum("Color").repr("u8").derive("Clone").derive("Copy");
+    e.new_variant("Red").discriminant("0xFF");
+    e.new_variant("Green").discriminant(2);
+    e.new_variant("Blue").discriminant(4);
+
+    let expect = r#"
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum Color {
+    Red = 0xFF,
+    Green = 2,
+    Blue = 4,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+#[should_panic(expected = "variant `Red` with fields cannot have a discriminant")]
+fn enum_discriminant_with_fields() {
+    let mut scope = Scope::new();
+
+    scope.new_enum("Color").new_variant("Red").tuple("u8").discriminant(1);
+    scope.to_string();
+}
+
+#[test]
+fn split_derives() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Foo")
+        .split_derives(true)
+        .derive("Debug")
+        .derive("Clone")
+        .derive("serde::Serialize");
+
+    let expect = r#"
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(serde::Serialize)]
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn tuple_field_with_annotation() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_struct("Wrapper")
+        .new_tuple_field("u32")
+        .annotation("#[serde(default)]")
+        .vis("pub");
+
+    let expect = r#"
+struct Wrapper(#[serde(default)] pub u32);"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn tuple_field_with_docs() {
+    let mut scope = Scope::new();
+
+    let mut inner = TupleField::new("Duration");
+    inner
+        .doc("The wrapped duration.")
+        .annotation("#[serde(with = \"humantime_serde\")]")
+        .vis("pub");
+
+    scope
+        .new_struct("Timeout")
+        .push_tuple_field(inner)
+        .tuple_field(None, "bool");
+
+    let expect = r#"
+struct Timeout(
+    /// The wrapped duration.
+    #[serde(with = "humantime_serde")]
+    pub Duration,
+    bool,
+);"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn enum_generate_from_str() {
+    let mut scope = Scope::new();
+
+    let mut e = Enum::new("Mode");
+    e.new_variant("Fast");
+    e.new_variant("Safe").annotation("#[serde(rename = \"safe-mode\")]");
+
+    scope.push_impl(e.generate_from_str("String"));
+
+    let expect = r#"
+impl std::str::FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Fast" => Ok(Self::Fast),
+            "safe-mode" => Ok(Self::Safe),
+            _ => Err(s.into()),
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn enum_generate_from_str_rename_all() {
+    let mut scope = Scope::new();
+
+    let mut e = Enum::new("Mode");
+    e.serde_rename_all("SCREAMING-KEBAB-CASE");
+    e.new_variant("FastMode");
+    e.new_variant("Safe").annotation("#[serde(rename = \"safe\")]");
+
+    scope.push_impl(e.generate_from_str("String"));
+
+    let expect = r#"
+impl std::str::FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "FAST-MODE" => Ok(Self::FastMode),
+            "safe" => Ok(Self::Safe),
+            _ => Err(s.into()),
+        }
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn tuple_struct_with_field_vis() {
+    let mut scope = Scope::new();
+
+    scope.new_struct("Meters").vis("pub").tuple_field_vis("pub", "f64");
+    scope.new_struct("Pair").tuple_field(None, "u8").tuple_field_vis(Visibility::PubCrate, "u8");
+
+    let expect = r#"
+pub struct Meters(pub f64);
+
+struct Pair(u8, pub(crate) u8);"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn imports_are_not_parsed_as_types() {
+    let mut scope = Scope::new();
+
+    scope.push_import("foo", "Bar", Some("BarAlias"));
+    scope.push_import("foo::baz", "Qux<'a>", None);
+    scope.push_import("foo::baz", "Quux", None);
+
+    let expect = r#"
+use foo::Bar as BarAlias;
+use foo::baz::{Qux<'a>, Quux};"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn trait_alias() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_trait_alias("Service")
+        .vis("pub")
+        .bound("Send")
+        .bound("Sync")
+        .bound("'static");
+
+    scope
+        .new_trait_alias("Handler")
+        .generic("T")
+        .bound("Fn(T) -> T")
+        .where_bound("T", "Clone");
+
+    let expect = r#"
+trait Handler<T> = Fn(T) -> T
+where T: Clone;
+
+pub trait Service = Send + Sync + 'static;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_unsafe_block() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("copy")
+        .arg("src", "*const u8")
+        .arg("dst", "*mut u8")
+        .line("let len = 4;")
+        .unsafe_block(|b| {
+            b.line("std::ptr::copy_nonoverlapping(src, dst, len);");
+            b.line("*dst = 0;");
+        });
+
+    let expect = r#"
+fn copy(src: *const u8, dst: *mut u8) {
+    let len = 4;
+    unsafe {
+        std::ptr::copy_nonoverlapping(src, dst, len);
+        *dst = 0;
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn trait_with_required_and_provided_fns() {
+    let mut scope = Scope::new();
+
+    let mut len = Function::new("len");
+    len.arg_ref_self().ret("usize").remove_body();
+
+    let trt = scope.new_trait("Collection");
+    trt.push_fn(len);
+    trt.new_fn("is_empty")
+        .arg_ref_self()
+        .ret("bool")
+        .line("self.len() == 0");
+
+    let expect = r#"
+trait Collection {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_const_generic_and_where_clause() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_fn("zeros")
+        .vis("pub")
+        .const_generic("N", "usize")
+        .ret("[u8; N]")
+        .bound("[(); N]", "Sized")
+        .line("[0; N]");
+
+    scope
+        .new_fn("filled")
+        .vis("pub")
+        .const_generic("N", "usize")
+        .generic("T")
+        .arg("value", "T")
+        .ret("[T; N]")
+        .bound("T", "Copy")
+        .bound("[T; N]", "Default")
+        .line("[value; N]");
+
+    let expect = r#"
+pub fn filled<T, const N: usize>(value: T) -> [T; N]
+where T: Copy,
+      [T; N]: Default,
+{
+    [value; N]
+}
+
+pub fn zeros<const N: usize>() -> [u8; N]
+where [(); N]: Sized,
+{
+    [0; N]
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn trait_with_generic_associated_types() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    let trt = scope.new_trait("Container");
+    trt.associated_type("Item").bound("Clone").bound("Send");
+    trt.associated_type("Iter")
+        .lifetime("a")
+        .bound("Iterator<Item = &'a Self::Item>")
+        .where_bound("Self", "'a");
+    trt.new_fn("iter")
+        .arg_ref_self()
+        .ret("Self::Iter<'_>");
+
+    scope
+        .new_impl("Bytes")
+        .impl_trait("Container")
+        .associate_type("Item", "u8")
+        .associate_type("Iter<'a>", "std::slice::Iter<'a, u8>")
+        .new_fn("iter")
+        .arg_ref_self()
+        .ret("Self::Iter<'_>")
+        .line("self.0.iter()");
+
+    let expect = r#"
+trait Container {
+    type Item: Clone + Send;
+    type Iter<'a>: Iterator<Item = &'a Self::Item>
+    where Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+impl Container for Bytes {
+    type Item = u8;
+    type Iter<'a> = std::slice::Iter<'a, u8>;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter()
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_generic_associated_type() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Bytes");
+    imp.impl_trait("Container");
+    imp.new_associate_type("Iter", "std::slice::Iter<'a, T>")
+        .lifetime("a")
+        .generic("T")
+        .where_bound("Self", "'a")
+        .where_bound("T", "'a");
+
+    let expect = r#"
+impl Container for Bytes {
+    type Iter<'a, T> = std::slice::Iter<'a, T>
+    where Self: 'a,
+          T: 'a;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn scope_docs() {
+    let mut scope = Scope::new();
+    scope.doc("Generated bindings.\n\nDo not edit.");
+    scope.no_std();
+    scope.new_struct("Foo");
+
+    let mut other = Scope::new();
+    other.doc("More bindings.");
+    other.new_struct("Bar");
+    scope.append(&other);
+
+    let expect = r#"
+//! Generated bindings.
+//!
+//! Do not edit.
+//! More bindings.
+#![no_std]
+
+struct Bar;
+
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn module_inner_docs() {
+    let mut scope = Scope::new();
+
+    let module = scope.new_module("foo");
+    module.doc("outer").inner_doc("inner\nsecond line");
+    module.new_struct("Bar");
+
+    let expect = r#"
+/// outer
+mod foo {
+    //! inner
+    //! second line
+
+    struct Bar;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn glob_and_self_imports() {
+    let mut scope = Scope::new();
+    scope.sort_imports(true);
+
+    scope.import_glob("foo");
+    scope.push_import("foo", "Bar", None);
+    scope.push_import("a::b", "C", None);
+    scope.push_import("a::b", "self", None);
+
+    scope.new_module("tests").import_glob("super").new_fn("it_works");
+
+    let expect = r#"
+use a::b::{self, C};
+use foo::*;
+use foo::Bar;
+
+mod tests {
+    use super::*;
+
+    fn it_works() {
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn lone_self_imports() {
+    let mut scope = Scope::new();
+
+    scope.push_import("a::b", "self", None);
+    scope.push_import("c::d", "self", Some("e"));
+    scope.new_import("f", "self", Some("g")).vis("pub");
+
+    let expect = r#"
+use a::b;
+use c::d as e;
+pub use f as g;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn nested_imports() {
+    let mut scope = Scope::new();
+    scope.sort_imports(true).nest_imports(true);
+
+    scope.push_import("std::io", "Write", None);
+    scope.push_import("std", "fmt", None);
+    scope.push_import("std::io", "self", None);
+    scope.push_import("std::collections", "HashMap", Some("Map"));
+    scope.push_import("serde", "de::DeserializeOwned", None);
+    scope.push_import("crate::model", "self", None);
+    scope.import_glob("super");
+    scope.new_import("crate::model", "User", None).vis("pub");
+    scope.new_import("crate::model", "Group", None).vis("pub");
+
+    let expect = r#"
+use crate::model;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap as Map, fmt, io::{self, Write}};
+use super::*;
+pub use crate::model::{Group, User};"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn nested_type_path_import() {
+    let mut scope = Scope::new();
+
+    scope.push_import("serde", "de::DeserializeOwned", None);
+    scope.push_import("serde::de", "DeserializeOwned", None);
+    scope.push_import("serde::de", "Visitor", None);
+
+    let expect = r#"
+use serde::de::{DeserializeOwned, Visitor};"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn configurable_indentation() {
+    for (indent, pad) in [(Indent::Spaces(2), "  "), (Indent::Spaces(4), "    "), (Indent::Tabs, "\t")] {
+        let mut scope = Scope::new();
+        let mut options = FmtOptions::new();
+        options.indent(indent);
+        scope.set_fmt_options(options);
+
+        scope
+            .new_module("foo")
+            .new_struct("Foo")
+            .field("one", "usize")
+            .field("two", "String");
+
+        let expect = format!(
+            "mod foo {{\n{p}struct Foo {{\n{p}{p}one: usize,\n{p}{p}two: String,\n{p}}}\n}}",
+            p = pad
+        );
+
+        assert_eq!(scope.to_string(), expect);
+    }
+}
+
+#[test]
+fn formatter_with_indent() {
+    let mut scope = Scope::new();
+    scope.new_struct("Foo").field("one", "usize");
+
+    let mut ret = String::new();
+    scope.fmt(&mut Formatter::with_indent(&mut ret, Indent::Tabs)).unwrap();
+
+    assert_eq!(ret, "struct Foo {\n\tone: usize,\n}\n");
+}
+
+#[test]
+fn multiline_fn_line_in_impl() {
+    let mut scope = Scope::new();
+
+    scope
+        .new_impl("Foo")
+        .new_fn("bar")
+        .line("let x = 1;\nx + 1");
+
+    let expect = r#"
+impl Foo {
+    fn bar() {
+        let x = 1;
+        x + 1
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn fn_with_annotated_arg() {
+    let mut scope = Scope::new();
+
+    let func = scope.new_fn("foo");
+    func.new_arg("x", "u32").annotation("#[allow(unused)]");
+    func.arg("y", "bool");
+
+    let expect = r#"
+fn foo(#[allow(unused)] x: u32, y: bool) {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn append_skips_duplicates() {
+    let mut scope = Scope::new();
+    scope.push_import("std::fmt", "Write", None);
+    scope.new_struct("Foo").field("one", "usize");
+
+    let mut other = Scope::new();
+    other.push_import("std::fmt", "Write", None);
+    other.push_import("std::fmt", "Debug", None);
+    other.new_struct("Foo").field("one", "usize");
+    other.new_struct("Bar");
+    scope.append(&other);
+
+    let expect = r#"
+use std::fmt::{Write, Debug};
+
+struct Bar;
+
+struct Foo {
+    one: usize,
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn append_compares_unformattable_items() {
+    // an empty union and a trait alias without bounds cannot be formatted
+    let mut scope = Scope::new();
+    scope.new_union("Empty");
+    scope.new_trait_alias("Unbounded");
+
+    let mut other = Scope::new();
+    other.new_union("Empty");
+    other.new_trait_alias("Unbounded");
+    other.new_trait_alias("Other");
+    scope.append(&other);
+
+    assert_eq!(scope.items().len(), 3);
+}
+
+#[test]
+fn impl_consts_and_fns_are_separated() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Foo");
+    imp.associate_const("ONE", "usize", "1", "pub");
+    imp.new_fn("one").ret("usize").line("Self::ONE");
+
+    let expect = r#"
+impl Foo {
+    pub const ONE: usize = 1;
+
+    fn one() -> usize {
+        Self::ONE
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn impl_groups_are_separated() {
+    let mut scope = Scope::new();
+
+    let imp = scope.new_impl("Foo");
+    imp.impl_trait("Bar");
+    imp.associate_const("ONE", "usize", "1", "");
+    imp.associate_const("TWO", "usize", "2", "");
+    imp.associate_type("Item", "u8");
+    imp.associate_type("Error", "String");
+    imp.new_fn("one");
+    imp.new_fn("two");
+
+    let expect = r#"
+impl Bar for Foo {
+    const ONE: usize = 1;
+    const TWO: usize = 2;
+
+    type Item = u8;
+    type Error = String;
+
+    fn one() {
+    }
+
+    fn two() {
+    }
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn unsafe_impl() {
+    let mut scope = Scope::new();
+    scope.new_impl("Wrapper").impl_trait("Sync").set_unsafe(true);
+
+    let expect = r#"
+unsafe impl Sync for Wrapper {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn negative_impl() {
+    let mut scope = Scope::new();
+    scope.new_impl("Raw").impl_trait("Send").negative();
+
+    assert_eq!(scope.to_string(), "impl !Send for Raw {}");
+}
+
+#[test]
+#[should_panic(expected = "negative impl for `Raw` cannot have items")]
+fn negative_impl_with_items() {
+    let mut scope = Scope::new();
+    scope.new_impl("Raw").impl_trait("Send").negative().new_fn("foo");
+    scope.to_string();
+}
+
+#[test]
+fn impl_associate_const_from_field() {
+    let mut scope = Scope::new();
+
+    let mut cst = Field::new("MAX", "u32");
+    cst.value("100").vis("pub").doc("The largest value.");
+
+    scope.new_impl("Foo").push_associate_const(cst);
+
+    let expect = r#"
+impl Foo {
+    /// The largest value.
+    pub const MAX: u32 = 100;
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn enum_variant_with_documented_fields() {
+    let mut scope = Scope::new();
+
+    let variant = scope.new_enum("Command").new_variant("Move");
+    variant.new_named("x", "i32").doc("x coord");
+    variant.new_named("y", "i32").doc("y coord").annotation("#[serde(default)]");
+
+    let expect = r#"
+enum Command {
+    Move {
+        /// x coord
+        x: i32,
+        /// y coord
+        #[serde(default)]
+        y: i32,
+    },
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn long_derive_list_wraps() {
+    let mut scope = Scope::new();
+    let mut options = FmtOptions::new();
+    options.max_width(80);
+    scope.set_fmt_options(options);
+
+    let s = scope.new_struct("Foo");
+    for name in [
+        "Debug", "Clone", "Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash", "Default", "Serialize",
+    ] {
+        s.derive(name);
+    }
+    scope.new_struct("Bar").derive("Debug").derive("Clone");
+
+    let expect = r#"
+#[derive(Debug, Clone)]
+struct Bar;
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    Serialize,
+)]
+struct Foo;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn long_where_bounds_wrap() {
+    let mut scope = Scope::new();
+    let mut options = FmtOptions::new();
+    options.max_width(40);
+    scope.set_fmt_options(options);
+
+    scope
+        .new_fn("foo")
+        .generic("T")
+        .generic("U")
+        .bound("T", "Clone")
+        .bound("T", "Debug")
+        .bound("T", "Send")
+        .bound("T", "Sync")
+        .bound("T", "'static")
+        .bound("U", "Clone");
+
+    let expect = r#"
+fn foo<T, U>()
+where T: Clone
+       + Debug
+       + Send
+       + Sync
+       + 'static,
+      U: Clone,
+{
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn cfg_gated_items() {
+    let mut scope = Scope::new();
+    scope.set_ordering(ItemOrdering::Insertion);
+
+    scope.push_import("std::fmt", "Debug", None);
+    scope.push_import("std::fmt", "Display", None);
+    scope.new_import("serde", "Serialize", None).cfg("feature = \"serde\"");
+    scope.new_import("serde", "Deserialize", None).cfg("feature = \"serde\"");
+
+    scope.new_fn("to_json").vis("pub").cfg("feature = \"serde\"");
+    scope.new_impl("Foo").impl_trait("Send").set_unsafe(true).cfg("unix");
+    scope.new_module("tests").cfg("test");
+
+    let expect = r#"
+use std::fmt::{Debug, Display};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "serde")]
+pub fn to_json() {
+}
+
+#[cfg(unix)]
+unsafe impl Send for Foo {
+}
+
+#[cfg(test)]
+mod tests {
+}"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+}
+
+#[test]
+fn module_declarations() {
+    let mut scope = Scope::new();
+
+    let models = scope.new_module("models");
+    models.vis("pub").doc("Data models.").set_declaration_only(true);
+    models.new_struct("User");
+
+    scope.new_module("util").cfg("test").set_declaration_only(true);
+
+    let expect = r#"
+/// Data models.
+pub mod models;
+
+#[cfg(test)]
+mod util;"#;
+
+    assert_eq!(scope.to_string(), &expect[1..]);
+    assert_eq!(scope.get_module_mut("models").unwrap().scope().to_string(), "struct User;");
+}